@@ -0,0 +1,190 @@
+use crate::commands::config::AppState;
+use crate::commands::symlinks::set_item_enabled;
+use crate::models::{AppConfig, LinkMode, Profile, SymlinkResult, UnmanagedKind, UnmanagedLink};
+use crate::services::{LinkService, TargetAudit};
+use std::path::Path;
+use tauri::State;
+
+/// List the symlinks in a profile's target folders that none of its enabled
+/// items accounts for: dangling links, links to unknown places and links to
+/// items of its sources that aren't linked there.
+#[tauri::command]
+pub async fn audit_targets(
+    state: State<'_, AppState>,
+    profile_id: String,
+) -> Result<Vec<UnmanagedLink>, String> {
+    // Scan the profile as of the last committed config
+    let (profile, others) = {
+        let config = state.config.get()?;
+        (
+            config.effective_profile(&profile_id)?,
+            other_profiles(&config, &profile_id),
+        )
+    };
+
+    tauri::async_runtime::spawn_blocking(move || TargetAudit::scan(&profile, &others))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Delete links found by the audit. Links any profile accounts for by now,
+/// links to items of other profiles and anything outside the profile's
+/// target folders are left alone.
+#[tauri::command]
+pub async fn delete_unmanaged_links(
+    state: State<'_, AppState>,
+    profile_id: String,
+    links: Vec<String>,
+) -> Result<Vec<SymlinkResult>, String> {
+    let queue = state.queue.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        queue.execute(move |config, _| {
+            let profile = config.effective_profile(&profile_id)?;
+            let others = other_profiles(config, &profile_id);
+            let expected = TargetAudit::expected_links(&profile, &others);
+
+            let results = links
+                .into_iter()
+                .map(|link| {
+                    let path = Path::new(&link);
+                    let result = match TargetAudit::inspect(&profile, &others, &expected, path) {
+                        Some(UnmanagedLink {
+                            kind: UnmanagedKind::Foreign,
+                            profile_name,
+                            ..
+                        }) => Err(format!(
+                            "Points at an item of profile \"{}\"",
+                            profile_name.unwrap_or_default()
+                        )),
+                        Some(_) => LinkService::remove(path).map_err(LinkService::format_error),
+                        None => Err("Not an unmanaged link".to_string()),
+                    };
+                    SymlinkResult {
+                        success: result.is_ok(),
+                        item_name: link,
+                        error: result.err(),
+                    }
+                })
+                .collect();
+            Ok(results)
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Take over links that point at items of the profile's sources by enabling
+/// the items. A link with another name or in a folder the source doesn't
+/// link into is kept by giving the item that link name or target folder.
+#[tauri::command]
+pub async fn adopt_unmanaged_links(
+    state: State<'_, AppState>,
+    profile_id: String,
+    links: Vec<String>,
+) -> Result<Vec<SymlinkResult>, String> {
+    let queue = state.queue.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        queue.execute(move |config, events| {
+            let mut results = Vec::new();
+            for link in links {
+                // Adopting one link can account for others, so each is checked
+                // against the config as it is by then
+                let profile = config.effective_profile(&profile_id)?;
+                let others = other_profiles(config, &profile_id);
+                let expected = TargetAudit::expected_links(&profile, &others);
+
+                let path = Path::new(&link);
+                let unmanaged = TargetAudit::inspect(&profile, &others, &expected, path);
+                let (source_id, item_name) = match unmanaged {
+                    Some(UnmanagedLink {
+                        kind: UnmanagedKind::Untracked,
+                        source_id: Some(source_id),
+                        item_name: Some(item_name),
+                        ..
+                    }) => (source_id, item_name),
+                    Some(_) => {
+                        results.push(SymlinkResult {
+                            success: false,
+                            item_name: link,
+                            error: Some(
+                                "Only links to items of a source can be adopted".to_string(),
+                            ),
+                        });
+                        continue;
+                    }
+                    None => {
+                        results.push(SymlinkResult {
+                            success: false,
+                            item_name: link,
+                            error: Some("Not an unmanaged link".to_string()),
+                        });
+                        continue;
+                    }
+                };
+
+                let source = config.source_mut(&source_id).ok_or("Source not found")?;
+                if source.is_enabled(&item_name) {
+                    results.push(SymlinkResult {
+                        success: false,
+                        item_name: link,
+                        error: Some(format!("\"{}\" is already linked elsewhere", item_name)),
+                    });
+                    continue;
+                }
+
+                // Match the item's settings to the link before enabling it
+                let previous = source.items.clone();
+                let link_name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let folder = path.parent().unwrap_or(path).to_path_buf();
+                let folders = source
+                    .link_paths(&item_name, &profile.base_path, &profile.variables)
+                    .iter()
+                    .filter_map(|link| link.parent().map(Path::to_path_buf))
+                    .collect::<Vec<_>>();
+                let relative = std::fs::read_link(path).is_ok_and(|target| target.is_relative());
+
+                let uses_link_name = source.link_name(&item_name) == link_name;
+                let item = source.item_mut(&item_name);
+                if !uses_link_name {
+                    item.link_name = Some(link_name);
+                }
+                if !folders.contains(&folder) {
+                    item.target_path = Some(folder.to_string_lossy().to_string());
+                }
+                item.link_mode = if relative {
+                    LinkMode::Relative
+                } else {
+                    LinkMode::Absolute
+                };
+
+                let result =
+                    set_item_enabled(config, events, &profile_id, &source_id, item_name, true)?;
+                if !result.success {
+                    let source = config.source_mut(&source_id).ok_or("Source not found")?;
+                    source.items = previous;
+                }
+                results.push(SymlinkResult {
+                    item_name: link,
+                    ..result
+                });
+            }
+            Ok(results)
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Compose every profile but the given one. Profiles with a broken include
+/// are skipped.
+fn other_profiles(config: &AppConfig, profile_id: &str) -> Vec<Profile> {
+    config
+        .profiles
+        .iter()
+        .filter(|profile| profile.id != profile_id)
+        .filter_map(|profile| config.effective_profile(&profile.id).ok())
+        .collect()
+}
@@ -1,202 +1,623 @@
-use crate::models::{AppConfig, Profile, Source};
-use crate::services::ConfigService;
+use crate::commands::filesystem::item_names;
+use crate::commands::symlinks::{changed_links, push_item_statuses, relink_all, sync_source_items};
+use crate::models::{
+    AppConfig, ConfigIssue, Profile, ProfilePatch, Source, SourcePatch, SourceRefresh,
+};
+use crate::services::link_service::LinkEntry;
+use crate::services::{
+    ConfigValidator, LinkService, ManifestService, OperationQueue, OperationRegistry, SharedConfig,
+};
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
-use std::sync::Mutex;
 use tauri::State;
+use uuid::Uuid;
 
 pub struct AppState {
-    pub config: Mutex<AppConfig>,
-    pub config_path: PathBuf,
+    pub config: SharedConfig,
+    pub queue: OperationQueue,
+    pub operations: OperationRegistry,
+    pub snapshot_dir: PathBuf,
+    /// Problems loading the config file at startup, reported along with
+    /// those found by validating it.
+    pub load_issues: Vec<ConfigIssue>,
 }
 
-fn save_config_internal(state: &State<'_, AppState>) -> Result<(), String> {
-    let config = state.config.lock().map_err(|e| e.to_string())?;
-    ConfigService::save(&state.config_path, &config)
+#[tauri::command]
+pub fn load_config(state: State<'_, AppState>) -> Result<AppConfig, String> {
+    let config = state.config.get()?;
+    Ok(AppConfig::clone(&config))
 }
 
 #[tauri::command]
-pub fn load_config(state: State<'_, AppState>) -> Result<AppConfig, String> {
-    let config = state.config.lock().map_err(|e| e.to_string())?;
-    Ok(config.clone())
+pub async fn save_config(state: State<'_, AppState>) -> Result<(), String> {
+    let queue = state.queue.clone();
+    tauri::async_runtime::spawn_blocking(move || queue.execute(|_, _| Ok(())))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Check the config for problems such as duplicate IDs, missing paths or
+/// sources that would link into themselves. A config file that couldn't be
+/// loaded at startup is reported first.
+#[tauri::command]
+pub fn validate_config(state: State<'_, AppState>) -> Result<Vec<ConfigIssue>, String> {
+    let config = state.config.get()?;
+    let mut issues = state.load_issues.clone();
+    issues.extend(ConfigValidator::validate(&config));
+    Ok(issues)
+}
+
+/// Re-read the config file, picking up edits made outside the app.
+#[tauri::command]
+pub async fn reload_config(state: State<'_, AppState>) -> Result<AppConfig, String> {
+    let queue = state.queue.clone();
+    tauri::async_runtime::spawn_blocking(move || queue.reload_from_disk())
+        .await
+        .map_err(|e| e.to_string())??;
+    load_config(state)
 }
 
+/// Settle a `config-conflict` by keeping either the file on disk or the
+/// in-memory config.
 #[tauri::command]
-pub fn save_config(state: State<'_, AppState>) -> Result<(), String> {
-    save_config_internal(&state)
+pub async fn resolve_config_conflict(
+    state: State<'_, AppState>,
+    keep_disk: bool,
+) -> Result<(), String> {
+    let queue = state.queue.clone();
+    tauri::async_runtime::spawn_blocking(move || queue.resolve_conflict(keep_disk))
+        .await
+        .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
-pub fn create_profile(
+pub async fn create_profile(
     state: State<'_, AppState>,
     name: String,
     base_path: String,
 ) -> Result<Profile, String> {
-    let profile = Profile::new(name, base_path);
+    let queue = state.queue.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        queue.execute(move |config, _| {
+            let profile = Profile::new(name, base_path);
+            config.profiles.push(profile.clone());
+            Ok(profile)
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
 
-    {
-        let mut config = state.config.lock().map_err(|e| e.to_string())?;
-        config.profiles.push(profile.clone());
-    }
+/// Copy a profile with all of its sources under new IDs. No links are
+/// created until the copy is applied.
+#[tauri::command]
+pub async fn duplicate_profile(
+    state: State<'_, AppState>,
+    profile_id: String,
+    name: Option<String>,
+    base_path: Option<String>,
+) -> Result<Profile, String> {
+    let queue = state.queue.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        queue.execute(move |config, _| {
+            let mut profile = config
+                .profiles
+                .iter()
+                .find(|p| p.id == profile_id)
+                .cloned()
+                .ok_or("Profile not found")?;
 
-    save_config_internal(&state)?;
-    Ok(profile)
+            profile.id = Uuid::new_v4().to_string();
+            profile.name = copy_name(name, &profile.name);
+            if let Some(base_path) = base_path.filter(|path| !path.trim().is_empty()) {
+                profile.base_path = base_path;
+            }
+            let new_ids = profile
+                .sources
+                .iter()
+                .map(|source| (source.id.clone(), Uuid::new_v4().to_string()))
+                .collect::<HashMap<_, _>>();
+            for source in &mut profile.sources {
+                source.id = new_ids[&source.id].clone();
+                source.remap_item_refs(&new_ids);
+                // The copy may point elsewhere, so it learns its items anew
+                source.identities.clear();
+            }
+
+            config.profiles.push(profile.clone());
+            Ok(profile)
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
-pub fn update_profile(state: State<'_, AppState>, profile: Profile) -> Result<Profile, String> {
-    {
-        let mut config = state.config.lock().map_err(|e| e.to_string())?;
-        if let Some(existing) = config.profiles.iter_mut().find(|p| p.id == profile.id) {
-            *existing = profile.clone();
-        } else {
-            return Err("Profile not found".to_string());
-        }
-    }
+pub async fn update_profile(
+    state: State<'_, AppState>,
+    profile_id: String,
+    patch: ProfilePatch,
+) -> Result<Profile, String> {
+    let queue = state.queue.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        queue.execute(move |config, _| {
+            let profile = config
+                .profiles
+                .iter_mut()
+                .find(|p| p.id == profile_id)
+                .ok_or("Profile not found")?;
+            profile.name = patch.name;
+            profile.base_path = patch.base_path;
+            profile.includes = patch.includes;
+            let profile = profile.clone();
 
-    save_config_internal(&state)?;
-    Ok(profile)
+            // Refuse includes that are missing or go in circles
+            config.effective_profile(&profile_id)?;
+            Ok(profile)
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
-pub fn delete_profile(state: State<'_, AppState>, profile_id: String) -> Result<(), String> {
-    // First, clean up symlinks for all sources in this profile
-    {
-        let config = state.config.lock().map_err(|e| e.to_string())?;
-        if let Some(profile) = config.profiles.iter().find(|p| p.id == profile_id) {
-            for source in &profile.sources {
-                let target_path = source.get_target_path(&profile.base_path);
-                for item_name in &source.enabled_items {
-                    let symlink_path = std::path::Path::new(target_path).join(item_name);
-                    if symlink_path.is_symlink() {
-                        let _ = remove_symlink(&symlink_path);
+pub async fn delete_profile(state: State<'_, AppState>, profile_id: String) -> Result<(), String> {
+    let queue = state.queue.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        queue.execute(move |config, events| {
+            // First, clean up the links of the profile, including those of the
+            // sources of profiles it includes. Links the active profile has as
+            // well, e.g. of a profile both include, are left in place.
+            let profile = config
+                .effective_profile(&profile_id)
+                .ok()
+                .or_else(|| config.profiles.iter().find(|p| p.id == profile_id).cloned());
+            let mut removed = Vec::new();
+            if let Some(profile) = profile {
+                let active_links = config
+                    .active_profile_id
+                    .as_ref()
+                    .filter(|id| **id != profile_id)
+                    .and_then(|id| config.effective_profile(id).ok())
+                    .map(|active| LinkService::profile_links(&active))
+                    .unwrap_or_default();
+
+                let (winners, overridden) = LinkService::layered_links(&profile);
+                for entry in winners.iter().chain(&overridden) {
+                    let shared = active_links
+                        .iter()
+                        .any(|active| active.link == entry.link && active.source == entry.source);
+                    if !shared
+                        && LinkService::points_to(&entry.link, &entry.source)
+                        && LinkService::remove(&entry.link).is_ok()
+                    {
+                        removed.push(entry.clone());
                     }
                 }
             }
-        }
-    }
 
-    {
-        let mut config = state.config.lock().map_err(|e| e.to_string())?;
-        config.profiles.retain(|p| p.id != profile_id);
-    }
+            config.profiles.retain(|p| p.id != profile_id);
+            for profile in &mut config.profiles {
+                profile.includes.retain(|id| *id != profile_id);
+            }
 
-    save_config_internal(&state)?;
-    Ok(())
+            // Sources of included profiles are still shown in their own
+            for profile in &config.profiles {
+                let owns =
+                    |entry: &&LinkEntry| profile.sources.iter().any(|s| s.id == entry.source_id);
+                if !removed.iter().any(|entry| owns(&entry)) {
+                    continue;
+                }
+                if let Ok(effective) = config.effective_profile(&profile.id) {
+                    push_item_statuses(events, &effective, removed.iter().filter(owns));
+                }
+            }
+            Ok(())
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
-pub fn create_source(
+pub async fn create_source(
     state: State<'_, AppState>,
     profile_id: String,
     name: String,
     source_path: String,
     target_path: Option<String>,
+) -> Result<SourceRefresh, String> {
+    let queue = state.queue.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        queue.execute(move |config, _| {
+            let mut source = Source::new(name, source_path, target_path);
+            let effective = config.effective_profile(&profile_id)?;
+
+            // Pick up the layout declared by the source itself
+            let manifest = ManifestService::load(&source.get_source_path(&effective.variables))?;
+            let results = match manifest {
+                Some(manifest) => {
+                    ManifestService::configure(&mut source, &manifest);
+                    ManifestService::enable_items(&effective, &mut source, &manifest)
+                }
+                None => Vec::new(),
+            };
+
+            let profile = config
+                .profiles
+                .iter_mut()
+                .find(|p| p.id == profile_id)
+                .ok_or("Profile not found")?;
+            profile.sources.push(source.clone());
+            Ok(SourceRefresh { source, results })
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Re-read a source's manifest, clean up or run the auto-enable rules as the
+/// source is set up to, and link any newly enabled items.
+#[tauri::command]
+pub async fn refresh_source(
+    state: State<'_, AppState>,
+    profile_id: String,
+    source_id: String,
+) -> Result<SourceRefresh, String> {
+    let queue = state.queue.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        queue.execute(move |config, events| {
+            let previous = LinkService::layered_links(&config.effective_profile(&profile_id)?);
+            let profile = config
+                .profiles
+                .iter_mut()
+                .find(|p| p.id == profile_id)
+                .ok_or("Profile not found")?;
+
+            let source = profile
+                .sources
+                .iter_mut()
+                .find(|s| s.id == source_id)
+                .ok_or("Source not found")?;
+
+            let manifest = ManifestService::load(&source.get_source_path(&profile.variables))?;
+            let mut results = match manifest {
+                Some(manifest) => {
+                    ManifestService::configure(source, &manifest);
+
+                    // Move the links of enabled items to the names and folders
+                    // the manifest declares now, and remove those it ignores
+                    let current =
+                        LinkService::layered_links(&config.effective_profile(&profile_id)?);
+                    let links = changed_links(&previous.0, &current.0);
+                    relink_all((&previous.0, &previous.1), (&current.0, &current.1), &links)?;
+
+                    let effective = config.effective_profile(&profile_id)?;
+                    let source = config.source_mut(&source_id).ok_or("Source not found")?;
+                    let results = ManifestService::enable_items(&effective, source, &manifest);
+
+                    // Report the items whose links moved, and those enabled now
+                    let effective = config.effective_profile(&profile_id)?;
+                    let (winners, overridden) = LinkService::layered_links(&effective);
+                    let changed = [&previous.0, &previous.1, &current.0, &current.1]
+                        .into_iter()
+                        .flatten()
+                        .filter(|entry| links.contains(&entry.link))
+                        .chain(winners.iter().chain(&overridden).filter(|entry| {
+                            entry.source_id == source_id
+                                && results
+                                    .iter()
+                                    .any(|r| r.success && r.item_name == entry.item_name)
+                        }));
+                    push_item_statuses(events, &effective, changed);
+                    results
+                }
+                None => Vec::new(),
+            };
+            results.extend(sync_source_items(config, events, &profile_id, &source_id)?);
+
+            let source = config.source_mut(&source_id).ok_or("Source not found")?;
+            Ok(SourceRefresh {
+                source: source.clone(),
+                results,
+            })
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Copy a source within its profile under a new ID, optionally with another
+/// target folder. No links are created until the profile is applied.
+#[tauri::command]
+pub async fn duplicate_source(
+    state: State<'_, AppState>,
+    profile_id: String,
+    source_id: String,
+    name: Option<String>,
+    target_path: Option<String>,
 ) -> Result<Source, String> {
-    let source = Source::new(name, source_path, target_path);
-
-    {
-        let mut config = state.config.lock().map_err(|e| e.to_string())?;
-        let profile = config
-            .profiles
-            .iter_mut()
-            .find(|p| p.id == profile_id)
-            .ok_or("Profile not found")?;
-        profile.sources.push(source.clone());
-    }
+    let queue = state.queue.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        queue.execute(move |config, _| {
+            let profile = config
+                .profiles
+                .iter_mut()
+                .find(|p| p.id == profile_id)
+                .ok_or("Profile not found")?;
 
-    save_config_internal(&state)?;
-    Ok(source)
+            let mut source = profile
+                .sources
+                .iter()
+                .find(|s| s.id == source_id)
+                .cloned()
+                .ok_or("Source not found")?;
+
+            source.id = Uuid::new_v4().to_string();
+            source.name = copy_name(name, &source.name);
+            source.identities.clear();
+            if let Some(target_path) = target_path.filter(|path| !path.trim().is_empty()) {
+                source.target_path = Some(target_path);
+            }
+
+            profile.sources.push(source.clone());
+            Ok(source)
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
-pub fn update_source(
+pub async fn update_source(
     state: State<'_, AppState>,
     profile_id: String,
-    source: Source,
+    source_id: String,
+    patch: SourcePatch,
 ) -> Result<Source, String> {
-    {
-        let mut config = state.config.lock().map_err(|e| e.to_string())?;
-        let profile = config
-            .profiles
-            .iter_mut()
-            .find(|p| p.id == profile_id)
-            .ok_or("Profile not found")?;
-
-        if let Some(existing) = profile.sources.iter_mut().find(|s| s.id == source.id) {
-            *existing = source.clone();
-        } else {
-            return Err("Source not found".to_string());
-        }
-    }
+    let queue = state.queue.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        queue.execute(move |config, events| {
+            let previous = LinkService::layered_links(&config.effective_profile(&profile_id)?);
+            let profile = config
+                .profiles
+                .iter_mut()
+                .find(|p| p.id == profile_id)
+                .ok_or("Profile not found")?;
 
-    save_config_internal(&state)?;
-    Ok(source)
+            let source = profile
+                .sources
+                .iter_mut()
+                .find(|s| s.id == source_id)
+                .ok_or("Source not found")?;
+
+            // Items already in the folder when rules are added don't count as
+            // new. The items aren't kept track of while there are no rules, so
+            // those seen before are outdated by then.
+            let reseed = !patch.auto_enable.is_empty()
+                && (source.auto_enable.is_empty() || source.known_items.is_none());
+
+            source.name = patch.name;
+            source.source_path = patch.source_path;
+            source.target_path = patch.target_path;
+            source.additional_targets = patch.additional_targets;
+            source.auto_enable = patch.auto_enable;
+            source.missing_items = patch.missing_items;
+            if reseed {
+                source.known_items =
+                    Some(item_names(source, &profile.variables).unwrap_or_default());
+            }
+            let source = source.clone();
+
+            // Move the links of enabled items to the folders they go to now
+            let effective = config.effective_profile(&profile_id)?;
+            let current = LinkService::layered_links(&effective);
+            let links = changed_links(&previous.0, &current.0);
+            relink_all((&previous.0, &previous.1), (&current.0, &current.1), &links)?;
+
+            let moved = [&previous.0, &previous.1, &current.0, &current.1]
+                .into_iter()
+                .flatten()
+                .filter(|entry| links.contains(&entry.link));
+            push_item_statuses(events, &effective, moved);
+            Ok(source)
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
-pub fn delete_source(
+pub async fn delete_source(
     state: State<'_, AppState>,
     profile_id: String,
     source_id: String,
 ) -> Result<(), String> {
-    // First, clean up symlinks for this source
-    {
-        let config = state.config.lock().map_err(|e| e.to_string())?;
-        if let Some(profile) = config.profiles.iter().find(|p| p.id == profile_id) {
-            if let Some(source) = profile.sources.iter().find(|s| s.id == source_id) {
-                let target_path = source.get_target_path(&profile.base_path);
-                for item_name in &source.enabled_items {
-                    let symlink_path = std::path::Path::new(target_path).join(item_name);
-                    if symlink_path.is_symlink() {
-                        let _ = remove_symlink(&symlink_path);
-                    }
+    let queue = state.queue.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        queue.execute(move |config, events| {
+            let profile = config
+                .profiles
+                .iter_mut()
+                .find(|p| p.id == profile_id)
+                .ok_or("Profile not found")?;
+
+            let symlink_paths = match profile.sources.iter().find(|s| s.id == source_id) {
+                Some(source) => source
+                    .enabled_items()
+                    .iter()
+                    .flat_map(|item_name| {
+                        source.link_paths(item_name, &profile.base_path, &profile.variables)
+                    })
+                    .collect(),
+                None => Vec::new(),
+            };
+
+            profile.sources.retain(|s| s.id != source_id);
+
+            // Clean up symlinks for this source. Names other sources provide as
+            // well are handed to the one with the highest priority left.
+            let effective = config.effective_profile(&profile_id)?;
+            let (winners, overridden) = LinkService::layered_links(&effective);
+            for symlink_path in &symlink_paths {
+                let owned = winners.iter().any(|entry| {
+                    entry.link == *symlink_path
+                        && LinkService::points_to(&entry.link, &entry.source)
+                });
+                if owned {
+                    continue;
+                }
+                if symlink_path.is_symlink() {
+                    let _ = LinkService::remove(symlink_path);
                 }
+                let _ = LinkService::reconcile(&winners, &overridden, symlink_path);
             }
-        }
-    }
 
-    {
-        let mut config = state.config.lock().map_err(|e| e.to_string())?;
-        let profile = config
-            .profiles
-            .iter_mut()
-            .find(|p| p.id == profile_id)
-            .ok_or("Profile not found")?;
-        profile.sources.retain(|s| s.id != source_id);
-    }
+            let handed_over = winners
+                .iter()
+                .chain(&overridden)
+                .filter(|entry| symlink_paths.contains(&entry.link));
+            push_item_statuses(events, &effective, handed_over);
+            Ok(())
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Put the sources of a profile in a new order. Sources later in the list
+/// take priority when several of them link the same name, so those links
+/// are handed to the new winners.
+#[tauri::command]
+pub async fn reorder_sources(
+    state: State<'_, AppState>,
+    profile_id: String,
+    source_ids: Vec<String>,
+) -> Result<Profile, String> {
+    let queue = state.queue.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        queue.execute(move |config, events| {
+            let profile = config
+                .profiles
+                .iter_mut()
+                .find(|p| p.id == profile_id)
+                .ok_or("Profile not found")?;
+
+            let complete = source_ids.len() == profile.sources.len()
+                && profile.sources.iter().all(|s| source_ids.contains(&s.id));
+            if !complete {
+                return Err("The new order has to list every source of the profile".to_string());
+            }
+            profile
+                .sources
+                .sort_by_key(|s| source_ids.iter().position(|id| *id == s.id));
+            let profile = profile.clone();
+
+            // Failures show on the items, with the previous link put back
+            let effective = config.effective_profile(&profile_id)?;
+            let (winners, overridden) = LinkService::layered_links(&effective);
+            let contested = overridden
+                .iter()
+                .map(|entry| entry.link.clone())
+                .collect::<HashSet<_>>();
+            for link in &contested {
+                let _ = LinkService::reconcile(&winners, &overridden, link);
+            }
 
-    save_config_internal(&state)?;
-    Ok(())
+            let affected = winners
+                .iter()
+                .chain(&overridden)
+                .filter(|entry| contested.contains(&entry.link));
+            push_item_statuses(events, &effective, affected);
+            Ok(profile)
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
+/// Save the enabled items of a source as a named preset, replacing any
+/// preset with the same name.
 #[tauri::command]
-pub fn set_active_profile(
+pub async fn save_preset(
     state: State<'_, AppState>,
-    profile_id: Option<String>,
-) -> Result<(), String> {
-    {
-        let mut config = state.config.lock().map_err(|e| e.to_string())?;
-        config.active_profile_id = profile_id;
+    profile_id: String,
+    source_id: String,
+    name: String,
+) -> Result<Source, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Preset name cannot be empty".to_string());
     }
 
-    save_config_internal(&state)?;
-    Ok(())
+    let queue = state.queue.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        queue.execute(move |config, _| {
+            let source = config
+                .profiles
+                .iter_mut()
+                .find(|p| p.id == profile_id)
+                .ok_or("Profile not found")?
+                .sources
+                .iter_mut()
+                .find(|s| s.id == source_id)
+                .ok_or("Source not found")?;
+
+            source.presets.insert(name, source.enabled_items());
+            Ok(source.clone())
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
-// Helper function for cross-platform symlink removal
-fn remove_symlink(path: &std::path::Path) -> std::io::Result<()> {
-    #[cfg(windows)]
-    {
-        let metadata = std::fs::symlink_metadata(path)?;
-        if metadata.is_dir() {
-            std::fs::remove_dir(path)
-        } else {
-            std::fs::remove_file(path)
-        }
-    }
+#[tauri::command]
+pub async fn delete_preset(
+    state: State<'_, AppState>,
+    profile_id: String,
+    source_id: String,
+    name: String,
+) -> Result<Source, String> {
+    let queue = state.queue.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        queue.execute(move |config, _| {
+            let source = config
+                .profiles
+                .iter_mut()
+                .find(|p| p.id == profile_id)
+                .ok_or("Profile not found")?
+                .sources
+                .iter_mut()
+                .find(|s| s.id == source_id)
+                .ok_or("Source not found")?;
 
-    #[cfg(unix)]
-    {
-        std::fs::remove_file(path)
-    }
+            source.presets.remove(&name).ok_or("Preset not found")?;
+            Ok(source.clone())
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn set_active_profile(
+    state: State<'_, AppState>,
+    profile_id: Option<String>,
+) -> Result<(), String> {
+    let queue = state.queue.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        queue.execute(move |config, _| {
+            config.active_profile_id = profile_id;
+            Ok(())
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Name a copy, falling back to the original name marked as a copy.
+fn copy_name(name: Option<String>, original: &str) -> String {
+    name.map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| format!("{} (copy)", original))
 }
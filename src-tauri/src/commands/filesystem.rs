@@ -1,12 +1,76 @@
 use crate::commands::config::AppState;
-use crate::models::{Item, ItemStatus};
-use std::collections::HashMap;
-use std::path::Path;
-use tauri::State;
+use crate::models::{Item, ItemStatus, ItemTarget, PathOwner, PathRole, Profile, Source};
+use crate::services::link_service::LinkEntry;
+use crate::services::manifest_service::MANIFEST_FILE_NAME;
+use crate::services::operation_registry::OperationContext;
+use crate::services::{AlternateIndex, Host, ItemIdentity};
+use crate::utils::PathExpander;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State};
+
+/// List the names of the items of a source that aren't ignored, sorted.
+pub(crate) fn item_names(
+    source: &Source,
+    variables: &BTreeMap<String, String>,
+) -> Result<Vec<String>, String> {
+    let source_path = source.get_source_path(variables);
+    let mut names = read_items(&source_path.to_string_lossy(), source.include_hidden)?
+        .into_iter()
+        .map(|item| item.name)
+        .filter(|name| !source.is_ignored(name))
+        .collect::<Vec<_>>();
+    names.sort();
+    Ok(names)
+}
+
+/// List the enabled items of a source that are no longer in its folder.
+/// Nothing counts as missing while the folder itself is unavailable, e.g. on
+/// a drive that isn't mounted.
+pub(crate) fn missing_items(source: &Source, variables: &BTreeMap<String, String>) -> Vec<String> {
+    let source_path = source.get_source_path(variables);
+    if !source_path.is_dir() {
+        return Vec::new();
+    }
+
+    let alternates = AlternateIndex::load(&source_path);
+    source
+        .enabled_items()
+        .into_iter()
+        .filter(|item_name| !alternates.contains(item_name))
+        .collect()
+}
+
+/// Remember how the enabled items of a source look on disk, so they can be
+/// followed when they are renamed, and forget those of disabled items.
+/// Items keep the identity they had when first seen.
+pub(crate) fn remember_identities(source: &mut Source, variables: &BTreeMap<String, String>) {
+    let source_path = source.get_source_path(variables);
+    let enabled_items = source.enabled_items();
+    source
+        .identities
+        .retain(|item_name, _| enabled_items.contains(item_name));
+
+    for item_name in enabled_items {
+        if source.identities.contains_key(&item_name) {
+            continue;
+        }
+        if let Some(identity) = ItemIdentity::of(&source_path.join(&item_name)) {
+            source.identities.insert(item_name, identity);
+        }
+    }
+}
 
 #[tauri::command]
 pub fn list_items(source_path: String) -> Result<Vec<Item>, String> {
-    let path = Path::new(&source_path);
+    read_items(&source_path, false)
+}
+
+/// List the items of a source directory, optionally including hidden ones.
+/// Version control folders and the source manifest are always skipped.
+/// Alternates like `name##os.linux` are listed as a single item `name`.
+pub(crate) fn read_items(source_path: &str, include_hidden: bool) -> Result<Vec<Item>, String> {
+    let path = Path::new(source_path);
 
     if !path.exists() {
         return Err(format!("Path does not exist: {}", source_path));
@@ -16,7 +80,8 @@ pub fn list_items(source_path: String) -> Result<Vec<Item>, String> {
         return Err(format!("Path is not a directory: {}", source_path));
     }
 
-    let mut items = Vec::new();
+    let mut items: Vec<Item> = Vec::new();
+    let mut alternates: HashMap<String, Vec<String>> = HashMap::new();
 
     let entries =
         std::fs::read_dir(path).map_err(|e| format!("Failed to read directory: {}", e))?;
@@ -30,7 +95,16 @@ pub fn list_items(source_path: String) -> Result<Vec<Item>, String> {
         let name = entry.file_name().to_string_lossy().to_string();
 
         // Skip hidden files/folders
-        if name.starts_with('.') {
+        let hidden = name.starts_with('.');
+        if hidden && (!include_hidden || name == ".git" || name == MANIFEST_FILE_NAME) {
+            continue;
+        }
+
+        if let Some((item_name, _)) = AlternateIndex::split(&name) {
+            alternates
+                .entry(item_name.to_string())
+                .or_default()
+                .push(name);
             continue;
         }
 
@@ -41,9 +115,37 @@ pub fn list_items(source_path: String) -> Result<Vec<Item>, String> {
             status: ItemStatus::Inactive,
             enabled: false,
             conflict_source: None,
+            overridden_by: None,
+            alternates: Vec::new(),
+            alternate: None,
+            targets: Vec::new(),
+            settings: None,
         });
     }
 
+    for (item_name, mut variants) in alternates {
+        variants.sort();
+        match items.iter_mut().find(|item| item.name == item_name) {
+            Some(item) => item.alternates = variants,
+            None => {
+                let first = path.join(&variants[0]);
+                items.push(Item {
+                    name: item_name,
+                    is_directory: first.is_dir(),
+                    source_path: first.to_string_lossy().to_string(),
+                    status: ItemStatus::Inactive,
+                    enabled: false,
+                    conflict_source: None,
+                    overridden_by: None,
+                    alternates: variants,
+                    alternate: None,
+                    targets: Vec::new(),
+                    settings: None,
+                });
+            }
+        }
+    }
+
     // Sort: directories first, then alphabetically
     items.sort_by(|a, b| {
         match (a.is_directory, b.is_directory) {
@@ -57,97 +159,426 @@ pub fn list_items(source_path: String) -> Result<Vec<Item>, String> {
 }
 
 #[tauri::command]
-pub fn get_items_with_status(
+pub async fn get_items_with_status(
+    app: AppHandle,
     state: State<'_, AppState>,
     profile_id: String,
     source_id: String,
+    operation_id: Option<String>,
 ) -> Result<Vec<Item>, String> {
-    let config = state.config.lock().map_err(|e| e.to_string())?;
+    // Compose the profile as of the last committed config
+    let profile = {
+        let config = state.config.get()?;
+        config.effective_profile(&profile_id)?
+    };
 
-    let profile = config
-        .profiles
-        .iter()
-        .find(|p| p.id == profile_id)
-        .ok_or("Profile not found")?;
+    let mut operation = state.operations.begin(&app, operation_id.clone());
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        scan_items(&profile, &source_id, &mut operation)
+    })
+    .await
+    .map_err(|e| e.to_string());
 
+    state.operations.finish(operation_id.as_deref());
+    result?
+}
+
+fn scan_items(
+    profile: &Profile,
+    source_id: &str,
+    operation: &mut OperationContext,
+) -> Result<Vec<Item>, String> {
     let source = profile
         .sources
         .iter()
         .find(|s| s.id == source_id)
         .ok_or("Source not found")?;
 
-    let target_path = source.get_target_path(&profile.base_path);
+    let source_path = source.get_source_path(&profile.variables);
 
     // Get all items from source directory
-    let mut items = list_items(source.source_path.clone())?;
+    let mut items = read_items(&source_path.to_string_lossy(), source.include_hidden)?;
+    items.retain(|item| !source.is_ignored(&item.name));
 
-    // Build map of what other sources have enabled in the same target
-    let mut other_enabled: HashMap<String, String> = HashMap::new();
+    let host = Host::current();
 
-    for other_source in &profile.sources {
-        if other_source.id == source_id {
+    // Build maps of the links other sources have enabled, by link path.
+    // Sources later in the profile take priority over earlier ones.
+    let position = profile
+        .sources
+        .iter()
+        .position(|s| s.id == source_id)
+        .unwrap_or_default();
+    let mut overriding: HashMap<PathBuf, String> = HashMap::new();
+    let mut overridable: HashSet<PathBuf> = HashSet::new();
+
+    for (index, other_source) in profile.sources.iter().enumerate() {
+        if index == position {
             continue;
         }
-        let other_target = other_source.get_target_path(&profile.base_path);
-        if other_target == target_path {
-            for item in &other_source.enabled_items {
-                other_enabled.insert(item.clone(), other_source.name.clone());
+        for item in other_source.enabled_items() {
+            if !host.applies(other_source, &item) {
+                continue;
+            }
+            for link in other_source.link_paths(&item, &profile.base_path, &profile.variables) {
+                if index > position {
+                    overriding.insert(link, other_source.name.clone());
+                } else {
+                    overridable.insert(link);
+                }
             }
         }
     }
 
+    operation.set_total(items.len());
+
     // Determine status for each item
     for item in &mut items {
-        item.enabled = source.enabled_items.contains(&item.name);
-
-        let symlink_path = Path::new(target_path).join(&item.name);
-
-        if let Some(conflict_source) = other_enabled.get(&item.name) {
-            item.status = ItemStatus::Conflict;
-            item.conflict_source = Some(conflict_source.clone());
-        } else if symlink_path.is_symlink() {
-            // Check if symlink points to our source
-            match std::fs::read_link(&symlink_path) {
-                Ok(link_target) => {
-                    let expected = Path::new(&source.source_path).join(&item.name);
-                    // Normalize paths for comparison
-                    let link_target_canonical = std::fs::canonicalize(&link_target).ok();
-                    let expected_canonical = std::fs::canonicalize(&expected).ok();
-
-                    match (link_target_canonical, expected_canonical) {
-                        (Some(link), Some(exp)) if link == exp => {
-                            item.status = ItemStatus::Active;
-                        }
-                        (None, _) => {
-                            // Symlink target doesn't exist
-                            item.status = ItemStatus::Broken;
-                        }
-                        _ => {
-                            // Points to different location - could be from another source
-                            // or manually created
-                            if !item.enabled {
-                                item.status = ItemStatus::Conflict;
-                                item.conflict_source = Some("External".to_string());
-                            } else {
-                                item.status = ItemStatus::Broken;
-                            }
+        if operation.is_cancelled() {
+            return Err("Operation cancelled".to_string());
+        }
+
+        item.enabled = source.is_enabled(&item.name);
+        item.settings = source.item(&item.name).cloned();
+
+        let symlink_paths = source.link_paths(&item.name, &profile.base_path, &profile.variables);
+
+        // Link the alternate that best matches this machine
+        let plain = source_path.join(&item.name);
+        let plain_exists = plain.exists() || plain.is_symlink();
+        let file_name = AlternateIndex::pick(&item.name, &item.alternates, plain_exists, &host);
+        if let Some(file_name) = file_name.as_ref().filter(|f| **f != item.name) {
+            item.source_path = source_path.join(file_name).to_string_lossy().to_string();
+            item.alternate = Some(file_name.clone());
+        }
+
+        if file_name.is_none() || !host.applies(source, &item.name) {
+            item.status = ItemStatus::NotApplicable;
+        } else {
+            let targets = symlink_paths
+                .iter()
+                .map(|symlink_path| {
+                    link_status(
+                        symlink_path,
+                        Path::new(&item.source_path),
+                        item.enabled,
+                        overriding.get(symlink_path),
+                        overridable.contains(symlink_path),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            let summary = summarize(&targets);
+            item.status = summary.status;
+            item.conflict_source = summary.conflict_source;
+            item.overridden_by = summary.overridden_by;
+            if targets.len() > 1 {
+                item.targets = targets;
+            }
+        }
+
+        operation.step(&symlink_paths[0]);
+    }
+
+    // Enabled items deleted from the folder are listed too, as their links
+    // are left dangling until they are cleaned up
+    for item_name in missing_items(source, &profile.variables) {
+        items.push(Item {
+            source_path: source_path.join(&item_name).to_string_lossy().to_string(),
+            is_directory: false,
+            status: ItemStatus::Missing,
+            enabled: true,
+            conflict_source: None,
+            overridden_by: None,
+            alternates: Vec::new(),
+            alternate: None,
+            targets: Vec::new(),
+            settings: source.item(&item_name).cloned(),
+            name: item_name,
+        });
+    }
+
+    Ok(items)
+}
+
+/// Determine the status of an item's link at `symlink_path`.
+fn link_status(
+    symlink_path: &Path,
+    expected: &Path,
+    enabled: bool,
+    overridden_by: Option<&String>,
+    overridable: bool,
+) -> ItemTarget {
+    let mut target = ItemTarget {
+        path: symlink_path.to_string_lossy().to_string(),
+        status: ItemStatus::Inactive,
+        conflict_source: None,
+        overridden_by: None,
+    };
+
+    if let Some(overridden_by) = overridden_by {
+        target.status = ItemStatus::Overridden;
+        target.overridden_by = Some(overridden_by.clone());
+    } else if symlink_path.is_symlink() {
+        // Check if symlink points to our source
+        match std::fs::read_link(symlink_path) {
+            Ok(_) => {
+                // Normalize paths for comparison, resolving through the
+                // link so relative link targets work
+                let link_target_canonical = std::fs::canonicalize(symlink_path).ok();
+                let expected_canonical = std::fs::canonicalize(expected).ok();
+
+                match (link_target_canonical, expected_canonical) {
+                    (Some(link), Some(exp)) if link == exp => {
+                        target.status = ItemStatus::Active;
+                    }
+                    (None, _) => {
+                        // Symlink target doesn't exist
+                        target.status = ItemStatus::Broken;
+                    }
+                    _ => {
+                        // Points to different location - could be from another source
+                        // or manually created. Enabling the item replaces the link
+                        // of a source with a lower priority.
+                        if enabled {
+                            target.status = ItemStatus::Broken;
+                        } else if overridable {
+                            target.status = ItemStatus::Inactive;
+                        } else {
+                            target.status = ItemStatus::Conflict;
+                            target.conflict_source = Some("External".to_string());
                         }
                     }
                 }
-                Err(_) => {
-                    item.status = ItemStatus::Broken;
+            }
+            Err(_) => {
+                target.status = ItemStatus::Broken;
+            }
+        }
+    } else if symlink_path.exists() {
+        // Regular file/folder exists with same name (not a symlink)
+        target.status = ItemStatus::Conflict;
+        target.conflict_source = Some("Existing file/folder".to_string());
+    }
+
+    target
+}
+
+/// Combine the statuses of an item's targets into one. Targets overridden by
+/// another source are left out, and an item linked in only some of the
+/// remaining ones counts as broken.
+fn summarize(targets: &[ItemTarget]) -> ItemTarget {
+    let severity = |status: &ItemStatus| match status {
+        ItemStatus::Active => 0,
+        ItemStatus::Inactive => 1,
+        ItemStatus::Broken => 2,
+        _ => 3,
+    };
+
+    let own = targets
+        .iter()
+        .filter(|target| target.status != ItemStatus::Overridden)
+        .collect::<Vec<_>>();
+    let Some(worst) = own.iter().max_by_key(|target| severity(&target.status)) else {
+        return targets[0].clone();
+    };
+
+    let mut summary = (*worst).clone();
+    if own.iter().any(|target| target.status != summary.status)
+        && severity(&summary.status) < severity(&ItemStatus::Broken)
+    {
+        summary.status = ItemStatus::Broken;
+    }
+    summary
+}
+
+/// Determine the status of an enabled item from the links of its profile as
+/// they are on disk, for items an operation linked or unlinked.
+pub(crate) fn linked_status(
+    winners: &[LinkEntry],
+    overridden: &[LinkEntry],
+    source_id: &str,
+    item_name: &str,
+) -> ItemStatus {
+    let own = |entry: &&LinkEntry| entry.source_id == source_id && entry.item_name == item_name;
+    let targets = winners
+        .iter()
+        .filter(own)
+        .map(|entry| link_status(&entry.link, &entry.source, true, None, false))
+        .chain(overridden.iter().filter(own).map(|entry| ItemTarget {
+            path: entry.link.to_string_lossy().to_string(),
+            status: ItemStatus::Overridden,
+            conflict_source: None,
+            overridden_by: None,
+        }))
+        .collect::<Vec<_>>();
+
+    // Items without links don't apply to this machine
+    if targets.is_empty() {
+        return ItemStatus::NotApplicable;
+    }
+    summarize(&targets).status
+}
+
+/// Find the items that manage a path in any profile: those it is a link of
+/// or lies in a linked folder of, and those whose source it is or lies in.
+/// Items of the active profile come first.
+#[tauri::command]
+pub async fn find_path_owner(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    operation_id: Option<String>,
+) -> Result<Vec<PathOwner>, String> {
+    let (profiles, active_profile_id) = {
+        let config = state.config.get()?;
+        // A profile with a broken include can't be composed, but shouldn't
+        // keep the others from being searched
+        let profiles = config
+            .profiles
+            .iter()
+            .filter_map(|profile| config.effective_profile(&profile.id).ok())
+            .collect::<Vec<_>>();
+        (profiles, config.active_profile_id.clone())
+    };
+
+    let mut operation = state.operations.begin(&app, operation_id.clone());
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        path_owners(
+            &path,
+            &profiles,
+            active_profile_id.as_deref(),
+            &mut operation,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string());
+
+    state.operations.finish(operation_id.as_deref());
+    result?
+}
+
+fn path_owners(
+    path: &str,
+    profiles: &[Profile],
+    active_profile_id: Option<&str>,
+    operation: &mut OperationContext,
+) -> Result<Vec<PathOwner>, String> {
+    let path = PathExpander::expand(path.trim(), &BTreeMap::new());
+    if !path.is_absolute() {
+        return Err("Enter a full path".to_string());
+    }
+    // A link is recognized by where it is, the source of an item by where
+    // the path leads
+    let lexical = resolve_folder(&path);
+    let resolved = PathExpander::normalize(&path);
+
+    let mut owners = Vec::new();
+    for profile in profiles {
+        for source in &profile.sources {
+            let mut matches = Vec::new();
+            for item_name in source.enabled_items() {
+                let link = source
+                    .link_paths(&item_name, &profile.base_path, &profile.variables)
+                    .iter()
+                    .map(|link| resolve_folder(link))
+                    .find(|link| lexical.starts_with(link));
+                if let Some(link) = link {
+                    matches.push((item_name, PathRole::Link, Some(link)));
+                }
+            }
+
+            let source_dir = PathExpander::normalize(&source.get_source_path(&profile.variables));
+            let file_name = [&resolved, &lexical]
+                .into_iter()
+                .find_map(|path| path.strip_prefix(&source_dir).ok()?.iter().next());
+            if let Some(file_name) = file_name {
+                let file_name = file_name.to_string_lossy();
+                let item_name = AlternateIndex::split(&file_name)
+                    .map_or(file_name.as_ref(), |(item_name, _)| item_name)
+                    .to_string();
+                if !matches.iter().any(|(name, _, _)| *name == item_name) {
+                    matches.push((item_name, PathRole::Source, None));
                 }
             }
-        } else if symlink_path.exists() {
-            // Regular file/folder exists with same name (not a symlink)
-            item.status = ItemStatus::Conflict;
-            item.conflict_source = Some("Existing file/folder".to_string());
-        } else {
-            item.status = ItemStatus::Inactive;
+
+            if matches.is_empty() {
+                continue;
+            }
+
+            // A source folder that can't be read, e.g. on a drive that isn't
+            // mounted, leaves the links of its enabled items broken
+            let items = match scan_items(profile, &source.id, operation) {
+                Ok(items) => items,
+                Err(_) if operation.is_cancelled() => {
+                    return Err("Operation cancelled".to_string());
+                }
+                Err(_) => matches
+                    .iter()
+                    .map(|(item_name, _, _)| unavailable_item(profile, source, item_name))
+                    .collect(),
+            };
+            for (item_name, role, link) in matches {
+                // Items the source doesn't list, such as ignored ones, aren't
+                // managed by it
+                let Some(item) = items.iter().find(|item| item.name == item_name) else {
+                    continue;
+                };
+                owners.push(PathOwner {
+                    profile_id: profile.id.clone(),
+                    profile_name: profile.name.clone(),
+                    active: active_profile_id == Some(profile.id.as_str()),
+                    source_id: source.id.clone(),
+                    source_name: source.name.clone(),
+                    item_name,
+                    role,
+                    link: link.map(|link| link.to_string_lossy().to_string()),
+                    enabled: item.enabled,
+                    status: item.status.clone(),
+                });
+            }
         }
     }
 
-    Ok(items)
+    owners.sort_by_key(|owner| !owner.active);
+    Ok(owners)
+}
+
+/// Describe an item of a source whose folder can't be read.
+fn unavailable_item(profile: &Profile, source: &Source, item_name: &str) -> Item {
+    let enabled = source.is_enabled(item_name);
+    Item {
+        name: item_name.to_string(),
+        source_path: source
+            .get_source_path(&profile.variables)
+            .join(item_name)
+            .to_string_lossy()
+            .to_string(),
+        is_directory: false,
+        status: if enabled {
+            ItemStatus::Broken
+        } else {
+            ItemStatus::Inactive
+        },
+        enabled,
+        conflict_source: None,
+        overridden_by: None,
+        alternates: Vec::new(),
+        alternate: None,
+        targets: Vec::new(),
+        settings: source.item(item_name).cloned(),
+    }
+}
+
+/// Resolve the folder a path is in, but not the path itself, so a link is
+/// compared by where it is rather than by what it points at.
+fn resolve_folder(path: &Path) -> PathBuf {
+    match (path.parent(), path.file_name()) {
+        (Some(folder), Some(file_name)) => PathExpander::normalize(folder).join(file_name),
+        _ => PathExpander::normalize(path),
+    }
 }
 
 #[tauri::command]
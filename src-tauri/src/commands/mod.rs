@@ -1,7 +1,15 @@
+pub mod audit;
 pub mod config;
 pub mod filesystem;
+pub mod operations;
+pub mod snapshots;
 pub mod symlinks;
+pub mod transfer;
 
+pub use audit::*;
 pub use config::*;
 pub use filesystem::*;
+pub use operations::*;
+pub use snapshots::*;
 pub use symlinks::*;
+pub use transfer::*;
@@ -0,0 +1,383 @@
+use crate::commands::config::AppState;
+use crate::commands::filesystem::remember_identities;
+use crate::commands::symlinks::push_item_statuses;
+use crate::models::{
+    ItemStatus, ItemStatusChanged, OperationReport, Profile, ProfileApplied, SymlinkResult,
+};
+use crate::services::link_service::LinkEntry;
+use crate::services::operation_registry::OperationContext;
+use crate::services::{AppEvent, DependencyService, LinkService};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use tauri::{AppHandle, State};
+
+/// Applies and reverts links for an operation, remembering every change so a
+/// cancelled operation can be rolled back.
+pub(crate) struct Deployment {
+    pub(crate) operation: OperationContext,
+    created: Vec<PathBuf>,
+    removed: Vec<LinkEntry>,
+    /// Entries whose link was created or removed, or failed to be.
+    changed: Vec<LinkEntry>,
+    results: Vec<SymlinkResult>,
+}
+
+impl Deployment {
+    pub(crate) fn new(operation: OperationContext) -> Self {
+        Self {
+            operation,
+            created: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+            results: Vec::new(),
+        }
+    }
+
+    /// Create the missing links. Returns false if the operation was cancelled.
+    pub(crate) fn link(&mut self, entries: &[LinkEntry]) -> bool {
+        for entry in entries {
+            if self.operation.is_cancelled() {
+                return false;
+            }
+
+            let result = if LinkService::points_to(&entry.link, &entry.source) {
+                Ok(())
+            } else {
+                self.changed.push(entry.clone());
+                LinkService::create_link(&entry.target(), &entry.link)
+                    .map(|_| self.created.push(entry.link.clone()))
+            };
+
+            self.record(entry, result);
+            self.operation.step(&entry.link);
+        }
+        true
+    }
+
+    /// Remove links that point at their source. Returns false if the
+    /// operation was cancelled.
+    pub(crate) fn unlink(&mut self, entries: &[LinkEntry]) -> bool {
+        for entry in entries {
+            if self.operation.is_cancelled() {
+                return false;
+            }
+
+            let result = if LinkService::points_to(&entry.link, &entry.source) {
+                self.changed.push(entry.clone());
+                LinkService::remove(&entry.link)
+                    .map(|_| self.removed.push(entry.clone()))
+                    .map_err(|e| e.to_string())
+            } else {
+                Ok(())
+            };
+
+            self.record(entry, result);
+            self.operation.step(&entry.link);
+        }
+        true
+    }
+
+    fn record(&mut self, entry: &LinkEntry, result: Result<(), String>) {
+        self.results.push(SymlinkResult {
+            success: result.is_ok(),
+            item_name: entry.item_name.clone(),
+            error: result.err(),
+        });
+    }
+
+    /// Undo every change made so far, newest first.
+    pub(crate) fn rollback(&mut self) {
+        for link in self.created.drain(..).rev() {
+            let _ = LinkService::remove(&link);
+        }
+        for entry in self.removed.drain(..).rev() {
+            let _ = LinkService::create(&entry.target(), &entry.link);
+        }
+    }
+
+    /// Queue `item-status-changed` for the items of a profile whose links
+    /// were changed.
+    pub(crate) fn push_item_statuses(&self, events: &mut Vec<AppEvent>, profile: &Profile) {
+        push_item_statuses(events, profile, &self.changed);
+    }
+
+    pub(crate) fn into_report(self, cancelled: bool) -> OperationReport {
+        OperationReport {
+            operation_id: self.operation.operation_id().to_string(),
+            cancelled,
+            results: self.results,
+        }
+    }
+}
+
+/// Create the links for every enabled item in a profile.
+#[tauri::command]
+pub async fn apply_profile(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    profile_id: String,
+    operation_id: String,
+) -> Result<OperationReport, String> {
+    let mut operation = state.operations.begin(&app, Some(operation_id.clone()));
+    let queue = state.queue.clone();
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        queue.execute(move |config, events| {
+            let profile = config.effective_profile(&profile_id)?;
+
+            // Links of overridden sources make way for the ones taking priority
+            let (entries, overridden) = LinkService::layered_links(&profile);
+            operation.set_total(overridden.len() + entries.len());
+
+            let mut deployment = Deployment::new(operation);
+            if !deployment.unlink(&overridden) || !deployment.link(&entries) {
+                deployment.rollback();
+                return Ok(deployment.into_report(true));
+            }
+
+            deployment.push_item_statuses(events, &profile);
+            events.push(AppEvent::ProfileApplied(ProfileApplied {
+                profile_id,
+                operation_id: deployment.operation.operation_id().to_string(),
+            }));
+            Ok(deployment.into_report(false))
+        })
+    })
+    .await
+    .map_err(|e| e.to_string());
+
+    state.operations.finish(Some(&operation_id));
+    result?
+}
+
+/// Remove the links of the currently active profile, create the links of the
+/// given one and make it active.
+#[tauri::command]
+pub async fn switch_profile(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    profile_id: String,
+    operation_id: String,
+) -> Result<OperationReport, String> {
+    let mut operation = state.operations.begin(&app, Some(operation_id.clone()));
+    let queue = state.queue.clone();
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        queue.execute(move |config, events| {
+            let next = config.effective_profile(&profile_id)?;
+            let previous = config
+                .active_profile_id
+                .as_ref()
+                .filter(|id| **id != profile_id)
+                .and_then(|id| config.effective_profile(id).ok());
+
+            // Links both profiles share, e.g. of a profile they both
+            // include, are left in place
+            let (to_link, overridden) = LinkService::layered_links(&next);
+            let mut to_unlink = previous
+                .as_ref()
+                .map(LinkService::profile_links)
+                .unwrap_or_default();
+            to_unlink.retain(|entry| {
+                !to_link
+                    .iter()
+                    .any(|next| next.link == entry.link && next.source == entry.source)
+            });
+            to_unlink.extend(overridden);
+            operation.set_total(to_unlink.len() + to_link.len());
+
+            let mut deployment = Deployment::new(operation);
+            if !deployment.unlink(&to_unlink) || !deployment.link(&to_link) {
+                deployment.rollback();
+                return Ok(deployment.into_report(true));
+            }
+
+            config.active_profile_id = Some(profile_id.clone());
+            if let Some(previous) = &previous {
+                deployment.push_item_statuses(events, previous);
+            }
+            deployment.push_item_statuses(events, &next);
+            events.push(AppEvent::ProfileApplied(ProfileApplied {
+                profile_id,
+                operation_id: deployment.operation.operation_id().to_string(),
+            }));
+            Ok(deployment.into_report(false))
+        })
+    })
+    .await
+    .map_err(|e| e.to_string());
+
+    state.operations.finish(Some(&operation_id));
+    result?
+}
+
+/// Switch a source to one of its presets, removing the links of items the
+/// preset doesn't enable and creating the missing ones.
+///
+/// Items that fail keep their previous state. Items that don't apply to this
+/// machine or are overridden by a source with a higher priority are only
+/// recorded.
+#[tauri::command]
+pub async fn apply_preset(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    profile_id: String,
+    source_id: String,
+    name: String,
+    operation_id: String,
+) -> Result<OperationReport, String> {
+    let mut operation = state.operations.begin(&app, Some(operation_id.clone()));
+    let queue = state.queue.clone();
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        queue.execute(move |config, events| {
+            let profile = config.effective_profile(&profile_id)?;
+            let source = profile
+                .sources
+                .iter()
+                .find(|s| s.id == source_id)
+                .ok_or("Source not found")?;
+            let preset = source.presets.get(&name).ok_or("Preset not found")?;
+
+            // Locked items keep their state
+            let enabled_items = source.enabled_items();
+            let unlocked = |item: &&String| !source.item(item).is_some_and(|i| i.locked);
+            let to_disable = enabled_items
+                .iter()
+                .filter(|item| !preset.contains(item))
+                .filter(unlocked)
+                .cloned()
+                .collect::<Vec<_>>();
+            let mut to_enable = Vec::new();
+            for item in preset.iter().filter(unlocked) {
+                if !enabled_items.contains(item) && !to_enable.contains(item) {
+                    to_enable.push(item.clone());
+                }
+            }
+
+            // Work out the links with the preset in place, so items of other
+            // sources give way to or take over the changed names by priority
+            let mut preset_profile = profile.clone();
+            let preset_source = preset_profile
+                .sources
+                .iter_mut()
+                .find(|s| s.id == source_id)
+                .ok_or("Source not found")?;
+            for item_name in to_disable.iter().chain(&to_enable) {
+                preset_source.set_enabled(item_name, to_enable.contains(item_name));
+            }
+
+            // Items incompatible with what is enabled once the preset is in
+            // place are refused, like when toggling them
+            let refused = to_enable
+                .iter()
+                .filter_map(|item_name| {
+                    let conflicts =
+                        DependencyService::conflicts(&preset_profile, &source_id, item_name);
+                    Some(SymlinkResult {
+                        success: false,
+                        item_name: item_name.clone(),
+                        error: Some(DependencyService::conflict_error(&conflicts)?),
+                    })
+                })
+                .collect::<Vec<_>>();
+            to_enable.retain(|item_name| !refused.iter().any(|r| r.item_name == *item_name));
+            if let Some(source) = preset_profile
+                .sources
+                .iter_mut()
+                .find(|s| s.id == source_id)
+            {
+                for result in &refused {
+                    source.set_enabled(&result.item_name, false);
+                }
+            }
+            let (winners, overridden) = LinkService::layered_links(&preset_profile);
+
+            let disable_links = LinkService::source_links(&profile, source, &to_disable);
+            let enable_links = LinkService::source_links(&profile, source, &to_enable);
+            let changed = disable_links
+                .iter()
+                .chain(&enable_links)
+                .map(|entry| entry.link.clone())
+                .collect::<HashSet<_>>();
+
+            let mut to_unlink = disable_links;
+            to_unlink.extend(
+                overridden
+                    .into_iter()
+                    .filter(|entry| changed.contains(&entry.link)),
+            );
+            let to_link = winners
+                .into_iter()
+                .filter(|entry| changed.contains(&entry.link))
+                .collect::<Vec<_>>();
+            operation.set_total(to_unlink.len() + to_link.len());
+
+            let mut deployment = Deployment::new(operation);
+            if !deployment.unlink(&to_unlink) || !deployment.link(&to_link) {
+                deployment.rollback();
+                return Ok(deployment.into_report(true));
+            }
+
+            // Results are in the order of the entries
+            let failed = to_unlink
+                .iter()
+                .chain(&to_link)
+                .zip(&deployment.results)
+                .filter(|(entry, result)| entry.source_id == source_id && !result.success)
+                .map(|(entry, _)| entry.item_name.clone())
+                .collect::<HashSet<_>>();
+            let linked = to_link
+                .iter()
+                .filter(|entry| entry.source_id == source_id)
+                .map(|entry| entry.item_name.as_str())
+                .collect::<HashSet<_>>();
+            let applicable = enable_links
+                .iter()
+                .map(|entry| entry.item_name.as_str())
+                .collect::<HashSet<_>>();
+
+            // The source may belong to an included profile
+            let source = config.source_mut(&source_id).ok_or("Source not found")?;
+            for item_name in to_disable.iter().chain(&to_enable) {
+                if failed.contains(item_name) {
+                    continue;
+                }
+
+                let enabled = to_enable.contains(item_name);
+                source.set_enabled(item_name, enabled);
+                events.push(AppEvent::ItemStatusChanged(ItemStatusChanged {
+                    profile_id: profile_id.clone(),
+                    source_id: source_id.clone(),
+                    item_name: item_name.clone(),
+                    enabled,
+                    status: if !enabled {
+                        ItemStatus::Inactive
+                    } else if linked.contains(item_name.as_str()) {
+                        ItemStatus::Active
+                    } else if applicable.contains(item_name.as_str()) {
+                        ItemStatus::Overridden
+                    } else {
+                        ItemStatus::NotApplicable
+                    },
+                }));
+            }
+            remember_identities(source, &profile.variables);
+
+            let mut report = deployment.into_report(false);
+            report.results.extend(refused);
+            Ok(report)
+        })
+    })
+    .await
+    .map_err(|e| e.to_string());
+
+    state.operations.finish(Some(&operation_id));
+    result?
+}
+
+/// Request cancellation of a running operation.
+#[tauri::command]
+pub fn cancel_operation(state: State<'_, AppState>, operation_id: String) -> bool {
+    state.operations.cancel(&operation_id)
+}
@@ -0,0 +1,193 @@
+use crate::commands::config::AppState;
+use crate::commands::operations::Deployment;
+use crate::models::{
+    AppConfig, OperationReport, Profile, ProfileApplied, SnapshotDiff, SnapshotSummary,
+};
+use crate::services::link_service::LinkEntry;
+use crate::services::{AppEvent, LinkService, SnapshotService};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State};
+
+/// Save the current state of a profile, including the links on disk.
+#[tauri::command]
+pub async fn create_snapshot(
+    state: State<'_, AppState>,
+    profile_id: String,
+    label: Option<String>,
+) -> Result<SnapshotSummary, String> {
+    let snapshot_dir = state.snapshot_dir.clone();
+    let label = label
+        .map(|label| label.trim().to_string())
+        .filter(|label| !label.is_empty());
+
+    // Run on the queue so the links aren't changing while they are recorded
+    let queue = state.queue.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        queue.execute(move |config, _| {
+            let (profile, effective) =
+                current_profile(config, &profile_id).ok_or("Profile not found")?;
+
+            let snapshot = SnapshotService::capture(&profile, &effective, label);
+            SnapshotService::save(&snapshot_dir, &snapshot)?;
+            Ok(SnapshotSummary::from(&snapshot))
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// List the snapshots of a profile, newest first.
+#[tauri::command]
+pub fn list_snapshots(state: State<'_, AppState>, profile_id: String) -> Vec<SnapshotSummary> {
+    SnapshotService::list(&state.snapshot_dir, &profile_id)
+}
+
+/// Show what restoring a snapshot would change.
+#[tauri::command]
+pub fn diff_snapshot(
+    state: State<'_, AppState>,
+    snapshot_id: String,
+) -> Result<SnapshotDiff, String> {
+    let snapshot = SnapshotService::load(&state.snapshot_dir, &snapshot_id)?;
+    let config = state.config.get()?;
+    let current = current_profile(&config, &snapshot.profile.id);
+    Ok(SnapshotService::diff(
+        &snapshot,
+        current
+            .as_ref()
+            .map(|(profile, effective)| (profile, effective)),
+    ))
+}
+
+/// Bring a profile and its links back to the state of a snapshot.
+///
+/// Links the snapshot doesn't have are removed and its links are recreated.
+/// The state before restoring is saved as a new snapshot, so a restore can
+/// be undone the same way.
+#[tauri::command]
+pub async fn restore_snapshot(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    snapshot_id: String,
+    operation_id: String,
+) -> Result<OperationReport, String> {
+    let snapshot_dir = state.snapshot_dir.clone();
+    let snapshot = SnapshotService::load(&snapshot_dir, &snapshot_id)?;
+
+    let mut operation = state.operations.begin(&app, Some(operation_id.clone()));
+    let queue = state.queue.clone();
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        queue.execute(move |config, events| {
+            let profile_id = snapshot.profile.id.clone();
+            let current = current_profile(config, &profile_id);
+            let backup = current.as_ref().map(|(profile, effective)| {
+                SnapshotService::capture(profile, effective, Some("Before restore".to_string()))
+            });
+            if let Some(backup) = &backup {
+                SnapshotService::save(&snapshot_dir, backup)?;
+            }
+
+            // Items of included profiles stay as they are there: their links
+            // only make way for the snapshot's, and are put back if the items
+            // are still enabled. Overridden links are included, in case they
+            // are still on disk.
+            let effective = current.map(|(_, effective)| effective);
+            let owns = |source_id: &str| snapshot.profile.sources.iter().any(|s| s.id == source_id);
+            let (winners, overridden) = effective
+                .as_ref()
+                .map(LinkService::layered_links)
+                .unwrap_or_default();
+            let to_unlink = winners
+                .into_iter()
+                .chain(overridden)
+                .filter(|entry| {
+                    match snapshot
+                        .links
+                        .iter()
+                        .find(|link| Path::new(&link.link) == entry.link)
+                    {
+                        Some(link) => Path::new(&link.target) != entry.source,
+                        None => owns(&entry.source_id),
+                    }
+                })
+                .collect::<Vec<_>>();
+            let included_sources = effective
+                .iter()
+                .flat_map(|profile| &profile.sources)
+                .filter(|source| !owns(&source.id));
+            let to_link = snapshot
+                .links
+                .iter()
+                .filter_map(|link| {
+                    let own = snapshot
+                        .profile
+                        .sources
+                        .iter()
+                        .find(|s| s.id == link.source_id);
+                    let item = match own {
+                        Some(source) => source.item(&link.item_name),
+                        None => Some(
+                            included_sources
+                                .clone()
+                                .find(|s| s.id == link.source_id)?
+                                .item(&link.item_name)
+                                .filter(|item| item.enabled)?,
+                        ),
+                    };
+                    Some(LinkEntry {
+                        source_id: link.source_id.clone(),
+                        item_name: link.item_name.clone(),
+                        source: PathBuf::from(&link.target),
+                        link: PathBuf::from(&link.link),
+                        mode: item.map(|item| item.link_mode).unwrap_or_default(),
+                    })
+                })
+                .collect::<Vec<_>>();
+            operation.set_total(to_unlink.len() + to_link.len());
+
+            let mut deployment = Deployment::new(operation);
+            if !deployment.unlink(&to_unlink) || !deployment.link(&to_link) {
+                deployment.rollback();
+                if let Some(backup) = &backup {
+                    let _ = SnapshotService::delete(&snapshot_dir, &backup.id);
+                }
+                return Ok(deployment.into_report(true));
+            }
+
+            match config.profiles.iter_mut().find(|p| p.id == profile_id) {
+                Some(profile) => *profile = snapshot.profile.clone(),
+                None => config.profiles.push(snapshot.profile.clone()),
+            }
+            if let Ok(profile) = config.effective_profile(&profile_id) {
+                deployment.push_item_statuses(events, &profile);
+            }
+
+            events.push(AppEvent::ProfileApplied(ProfileApplied {
+                profile_id,
+                operation_id: deployment.operation.operation_id().to_string(),
+            }));
+            Ok(deployment.into_report(false))
+        })
+    })
+    .await
+    .map_err(|e| e.to_string());
+
+    state.operations.finish(Some(&operation_id));
+    result?
+}
+
+#[tauri::command]
+pub fn delete_snapshot(state: State<'_, AppState>, snapshot_id: String) -> Result<(), String> {
+    SnapshotService::delete(&state.snapshot_dir, &snapshot_id)
+}
+
+/// Find a profile along with its effective profile, which is the profile
+/// itself if its includes can't be resolved.
+fn current_profile(config: &AppConfig, profile_id: &str) -> Option<(Profile, Profile)> {
+    let profile = config.profiles.iter().find(|p| p.id == profile_id)?;
+    let effective = config
+        .effective_profile(profile_id)
+        .unwrap_or_else(|_| profile.clone());
+    Some((profile.clone(), effective))
+}
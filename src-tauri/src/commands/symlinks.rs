@@ -1,203 +1,703 @@
 use crate::commands::config::AppState;
-use crate::models::{PermissionStatus, SymlinkResult};
-use crate::services::ConfigService;
-use std::path::Path;
+use crate::commands::filesystem::{item_names, linked_status, missing_items, remember_identities};
+use crate::models::{
+    AppConfig, ItemConfig, ItemStatus, ItemStatusChanged, MissingItemPolicy, PermissionStatus,
+    Profile, Source, SymlinkResult, ToggleCheck,
+};
+use crate::services::link_service::LinkEntry;
+use crate::services::{
+    AlternateIndex, AppEvent, DependencyService, Host, ItemIdentity, LinkService,
+};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use tauri::State;
 
-#[cfg(unix)]
-use std::os::unix::fs::symlink;
-
 #[cfg(windows)]
-use std::os::windows::fs::{symlink_dir, symlink_file};
+use std::os::windows::fs::symlink_file;
 
 #[tauri::command]
-pub fn toggle_item(
+pub async fn toggle_item(
     state: State<'_, AppState>,
     profile_id: String,
     source_id: String,
     item_name: String,
     enabled: bool,
 ) -> Result<SymlinkResult, String> {
-    let (_source_path, target_path, item_source_path) = {
-        let mut config = state.config.lock().map_err(|e| e.to_string())?;
-
-        let profile = config
-            .profiles
-            .iter_mut()
-            .find(|p| p.id == profile_id)
-            .ok_or("Profile not found")?;
-
-        let source = profile
-            .sources
-            .iter_mut()
-            .find(|s| s.id == source_id)
-            .ok_or("Source not found")?;
-
-        let target = source.get_target_path(&profile.base_path).to_string();
-        let src = source.source_path.clone();
-        let item_src = Path::new(&src).join(&item_name).to_string_lossy().to_string();
-
-        // Update enabled state
-        if enabled {
-            if !source.enabled_items.contains(&item_name) {
-                source.enabled_items.push(item_name.clone());
-            }
-        } else {
-            source.enabled_items.retain(|i| i != &item_name);
-        }
-
-        (src, target, item_src)
-    };
+    let queue = state.queue.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        queue.execute(move |config, events| {
+            set_item_enabled(config, events, &profile_id, &source_id, item_name, enabled)
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
 
-    // Save config
-    {
-        let config = state.config.lock().map_err(|e| e.to_string())?;
-        ConfigService::save(&state.config_path, &config)?;
-    }
+/// Enable or disable an item and create or remove its links. Failures to
+/// link are reported in the result and leave the config as it was.
+pub(crate) fn set_item_enabled(
+    config: &mut AppConfig,
+    events: &mut Vec<AppEvent>,
+    profile_id: &str,
+    source_id: &str,
+    item_name: String,
+    enabled: bool,
+) -> Result<SymlinkResult, String> {
+    let mut profile = config.effective_profile(profile_id)?;
 
-    // Handle the actual symlink
-    let item_source = Path::new(&item_source_path);
-    let symlink_path = Path::new(&target_path).join(&item_name);
+    let source = profile
+        .sources
+        .iter()
+        .find(|s| s.id == source_id)
+        .ok_or("Source not found")?;
 
+    if source.item(&item_name).is_some_and(|item| item.locked) {
+        return Ok(SymlinkResult {
+            success: false,
+            item_name,
+            error: Some("Item is locked".to_string()),
+        });
+    }
     if enabled {
-        // Create symlink
-        if symlink_path.exists() || symlink_path.is_symlink() {
-            // Rollback enabled state
-            rollback_enabled_state(&state, &profile_id, &source_id, &item_name)?;
+        let conflicts = DependencyService::conflicts(&profile, source_id, &item_name);
+        if let Some(error) = DependencyService::conflict_error(&conflicts) {
             return Ok(SymlinkResult {
                 success: false,
                 item_name,
-                error: Some("Target location already has a file or folder with this name".to_string()),
+                error: Some(error),
             });
         }
+    }
+
+    let host = Host::current();
+    let source_path = source.get_source_path(&profile.variables);
+    let symlink_paths = source.link_paths(&item_name, &profile.base_path, &profile.variables);
 
-        // Ensure target directory exists
-        if let Some(parent) = symlink_path.parent() {
-            if !parent.exists() {
-                std::fs::create_dir_all(parent)
-                    .map_err(|e| format!("Failed to create target directory: {}", e))?;
+    // Link the alternate that best matches this machine
+    let file_name = AlternateIndex::load(&source_path).resolve(&item_name, &host);
+    let applicable = file_name.is_some() && host.applies(source, &item_name);
+
+    let (previous_winners, previous_overridden) = LinkService::layered_links(&profile);
+    if let Some(source) = profile.sources.iter_mut().find(|s| s.id == source_id) {
+        source.set_enabled(&item_name, enabled);
+    }
+    let (winners, overridden) = LinkService::layered_links(&profile);
+    let owns = |winners: &[LinkEntry], link: &Path| {
+        winners
+            .iter()
+            .any(|entry| entry.link == link && entry.source_id == source_id)
+    };
+    let linked = symlink_paths.iter().any(|link| owns(&winners, link));
+
+    // Each name goes to the source with the highest priority that
+    // enables it. Items meant for other machines are only recorded, so
+    // they get linked where they apply.
+    let result = if enabled {
+        let mut done = Vec::new();
+        let mut result = Ok(());
+        for symlink_path in &symlink_paths {
+            result = LinkService::reconcile(&winners, &overridden, symlink_path);
+            if result.is_err() {
+                break;
             }
+            done.push(symlink_path);
         }
 
-        match create_symlink(item_source, &symlink_path) {
-            Ok(_) => Ok(SymlinkResult {
-                success: true,
-                item_name,
-                error: None,
-            }),
-            Err(e) => {
-                // Rollback enabled state
-                rollback_enabled_state(&state, &profile_id, &source_id, &item_name)?;
-
-                Ok(SymlinkResult {
-                    success: false,
-                    item_name,
-                    error: Some(format_symlink_error(e)),
-                })
+        // Put back the targets linked so far
+        if result.is_err() {
+            for symlink_path in done {
+                if owns(&winners, symlink_path) && !owns(&previous_winners, symlink_path) {
+                    let _ = LinkService::remove(symlink_path);
+                    let _ = LinkService::reconcile(
+                        &previous_winners,
+                        &previous_overridden,
+                        symlink_path,
+                    );
+                }
             }
         }
+        result
     } else {
-        // Remove symlink
-        if symlink_path.is_symlink() {
-            match remove_symlink(&symlink_path) {
-                Ok(_) => Ok(SymlinkResult {
-                    success: true,
-                    item_name,
-                    error: None,
-                }),
-                Err(e) => Ok(SymlinkResult {
-                    success: false,
-                    item_name,
-                    error: Some(e.to_string()),
-                }),
+        symlink_paths.iter().try_for_each(|symlink_path| {
+            // Keep the link if it belongs to the source that wins the name now
+            let keep = winners.iter().any(|entry| {
+                entry.link == *symlink_path && LinkService::points_to(&entry.link, &entry.source)
+            });
+            if symlink_path.is_symlink() && !keep {
+                LinkService::remove(symlink_path).map_err(LinkService::format_error)?;
             }
+            // Hand the name to a source with a lower priority. If that
+            // fails, it shows on the other source's item.
+            let _ = LinkService::reconcile(&winners, &overridden, symlink_path);
+            Ok(())
+        })
+    };
+
+    if let Err(error) = result {
+        return Ok(SymlinkResult {
+            success: false,
+            item_name,
+            error: Some(error),
+        });
+    }
+
+    // Record the change on the source, which may belong to an included
+    // profile
+    let source = config.source_mut(source_id).ok_or("Source not found")?;
+    source.set_enabled(&item_name, enabled);
+    remember_identities(source, &profile.variables);
+
+    events.push(AppEvent::ItemStatusChanged(ItemStatusChanged {
+        profile_id: profile_id.to_string(),
+        source_id: source_id.to_string(),
+        item_name: item_name.clone(),
+        enabled,
+        status: if !applicable {
+            ItemStatus::NotApplicable
+        } else if !enabled {
+            ItemStatus::Inactive
+        } else if linked {
+            ItemStatus::Active
         } else {
-            Ok(SymlinkResult {
-                success: true,
-                item_name,
-                error: None,
-            })
-        }
+            ItemStatus::Overridden
+        },
+    }));
+
+    Ok(SymlinkResult {
+        success: true,
+        item_name,
+        error: None,
+    })
+}
+
+/// Bring the enabled items of a source in line with its folder: follow the
+/// items that were renamed, drop the items that are gone when the source
+/// removes them automatically, then run its auto-enable rules.
+#[tauri::command]
+pub async fn sync_source(
+    state: State<'_, AppState>,
+    profile_id: String,
+    source_id: String,
+) -> Result<Vec<SymlinkResult>, String> {
+    let queue = state.queue.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        queue.execute(move |config, events| {
+            sync_source_items(config, events, &profile_id, &source_id)
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+pub(crate) fn sync_source_items(
+    config: &mut AppConfig,
+    events: &mut Vec<AppEvent>,
+    profile_id: &str,
+    source_id: &str,
+) -> Result<Vec<SymlinkResult>, String> {
+    let policy = config
+        .effective_profile(profile_id)?
+        .sources
+        .iter()
+        .find(|s| s.id == source_id)
+        .map(|source| source.missing_items)
+        .ok_or("Source not found")?;
+
+    // Renamed items are followed before anything is taken for deleted or new
+    let mut results = follow_renames(config, events, profile_id, source_id)?;
+    if policy == MissingItemPolicy::Remove {
+        results.extend(remove_missing_items(
+            config, events, profile_id, source_id, None,
+        )?);
     }
+    results.extend(auto_enable_items(config, events, profile_id, source_id)?);
+
+    let variables = config.effective_profile(profile_id)?.variables;
+    let source = config.source_mut(source_id).ok_or("Source not found")?;
+    remember_identities(source, &variables);
+    Ok(results)
 }
 
-fn rollback_enabled_state(
-    state: &State<'_, AppState>,
+/// Find the enabled items that were renamed in the source folder by their
+/// identity, and move their settings and links to the new names. Only items
+/// without settings of their own are taken as the new name of an item, and
+/// only if exactly one of them matches.
+fn follow_renames(
+    config: &mut AppConfig,
+    events: &mut Vec<AppEvent>,
     profile_id: &str,
     source_id: &str,
-    item_name: &str,
-) -> Result<(), String> {
-    {
-        let mut config = state.config.lock().map_err(|e| e.to_string())?;
-        if let Some(profile) = config.profiles.iter_mut().find(|p| p.id == profile_id) {
-            if let Some(source) = profile.sources.iter_mut().find(|s| s.id == source_id) {
-                source.enabled_items.retain(|i| i != item_name);
+) -> Result<Vec<SymlinkResult>, String> {
+    let profile = config.effective_profile(profile_id)?;
+    let source = profile
+        .sources
+        .iter()
+        .find(|s| s.id == source_id)
+        .ok_or("Source not found")?;
+
+    let missing = missing_items(source, &profile.variables)
+        .into_iter()
+        .filter_map(|name| Some((source.identities.get(&name)?.clone(), name)))
+        .collect::<Vec<_>>();
+    if missing.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Inodes are cheap to read, the content is only hashed when needed
+    struct Candidate {
+        name: String,
+        inode: Option<String>,
+        shape: Option<(bool, Option<u64>)>,
+        content: Option<Option<String>>,
+    }
+    let source_path = source.get_source_path(&profile.variables);
+    let mut candidates = item_names(source, &profile.variables)?
+        .into_iter()
+        .filter(|name| source.item(name).is_none())
+        .map(|name| {
+            let path = source_path.join(&name);
+            Candidate {
+                inode: ItemIdentity::inode(&path),
+                shape: ItemIdentity::shape(&path),
+                name,
+                content: None,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut renames = Vec::new();
+    for (identity, old_name) in missing {
+        let same_content = |candidate: &mut Candidate| {
+            let path = source_path.join(&candidate.name);
+            identity.content.is_some()
+                && *candidate
+                    .content
+                    .get_or_insert_with(|| ItemIdentity::content(&path))
+                    == identity.content
+        };
+
+        // An inode may be reused by an unrelated file once the item is gone,
+        // or come from another machine, so it has to be the same kind of
+        // entry with the same size, or have the same content
+        let mut matches = Vec::new();
+        for (index, candidate) in candidates.iter_mut().enumerate() {
+            if identity.inode.is_none() || candidate.inode != identity.inode {
+                continue;
+            }
+            let same_shape = candidate.shape.is_some_and(|(directory, size)| {
+                directory == identity.directory
+                    && (directory || (size.is_some() && size == identity.size))
+            });
+            if same_shape || same_content(candidate) {
+                matches.push(index);
             }
         }
+        if matches.is_empty() {
+            for (index, candidate) in candidates.iter_mut().enumerate() {
+                if same_content(candidate) {
+                    matches.push(index);
+                }
+            }
+        }
+
+        if let [index] = matches[..] {
+            renames.push((old_name, candidates.remove(index).name));
+        }
+    }
+
+    let mut results = Vec::new();
+    for (old_name, new_name) in renames {
+        results.push(rename_item(
+            config, events, profile_id, source_id, &old_name, new_name,
+        )?);
     }
-    let config = state.config.lock().map_err(|e| e.to_string())?;
-    ConfigService::save(&state.config_path, &config)
+    Ok(results)
 }
 
-#[cfg(unix)]
-fn create_symlink(source: &Path, target: &Path) -> std::io::Result<()> {
-    symlink(source, target)
+/// Move an item's settings and links to the name it was renamed to. The
+/// rename is recorded even if the new links can't be created, as the old
+/// name is gone either way.
+fn rename_item(
+    config: &mut AppConfig,
+    events: &mut Vec<AppEvent>,
+    profile_id: &str,
+    source_id: &str,
+    old_name: &str,
+    new_name: String,
+) -> Result<SymlinkResult, String> {
+    let mut profile = config.effective_profile(profile_id)?;
+    let source = profile
+        .sources
+        .iter_mut()
+        .find(|s| s.id == source_id)
+        .ok_or("Source not found")?;
+
+    let mut links = source.link_paths(old_name, &profile.base_path, &profile.variables);
+    source.rename_item(old_name, &new_name);
+    for link in source.link_paths(&new_name, &profile.base_path, &profile.variables) {
+        if !links.contains(&link) {
+            links.push(link);
+        }
+    }
+    let applicable = Host::current().applies(source, &new_name);
+    let (winners, overridden) = LinkService::layered_links(&profile);
+
+    // The links to the old name were left dangling by the rename
+    let result = links.iter().try_for_each(|link| {
+        let dangling = link.is_symlink() && !link.exists();
+        let to_old_name = std::fs::read_link(link)
+            .is_ok_and(|target| target.file_name().is_some_and(|name| name == old_name));
+        if dangling && to_old_name {
+            LinkService::remove(link).map_err(LinkService::format_error)?;
+        }
+        LinkService::reconcile(&winners, &overridden, link)
+    });
+    let linked = winners
+        .iter()
+        .any(|entry| entry.source_id == source_id && entry.item_name == new_name);
+
+    // The source may belong to an included profile, and items of any
+    // source may refer to it
+    for source in config.profiles.iter_mut().flat_map(|p| &mut p.sources) {
+        if source.id == source_id {
+            source.rename_item(old_name, &new_name);
+        } else {
+            source.rename_item_refs(source_id, old_name, &new_name);
+        }
+    }
+
+    events.push(AppEvent::ItemStatusChanged(ItemStatusChanged {
+        profile_id: profile_id.to_string(),
+        source_id: source_id.to_string(),
+        item_name: new_name.clone(),
+        enabled: true,
+        status: if !applicable {
+            ItemStatus::NotApplicable
+        } else if result.is_err() {
+            ItemStatus::Broken
+        } else if linked {
+            ItemStatus::Active
+        } else {
+            ItemStatus::Overridden
+        },
+    }));
+
+    Ok(SymlinkResult {
+        success: result.is_ok(),
+        item_name: new_name,
+        error: result.err(),
+    })
 }
 
-#[cfg(windows)]
-fn create_symlink(source: &Path, target: &Path) -> std::io::Result<()> {
-    if source.is_dir() {
-        symlink_dir(source, target)
-    } else {
-        symlink_file(source, target)
+/// Remove the links of enabled items that were deleted from the source
+/// folder and drop them from the config. Without `item_names`, every missing
+/// item is cleaned up.
+#[tauri::command]
+pub async fn clean_missing_items(
+    state: State<'_, AppState>,
+    profile_id: String,
+    source_id: String,
+    item_names: Option<Vec<String>>,
+) -> Result<Vec<SymlinkResult>, String> {
+    let queue = state.queue.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        queue.execute(move |config, events| {
+            remove_missing_items(
+                config,
+                events,
+                &profile_id,
+                &source_id,
+                item_names.as_deref(),
+            )
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Disable the missing items of a source, which removes their links and
+/// hands the names to sources with a lower priority, then drop their
+/// settings. Locked items are left alone.
+pub(crate) fn remove_missing_items(
+    config: &mut AppConfig,
+    events: &mut Vec<AppEvent>,
+    profile_id: &str,
+    source_id: &str,
+    item_names: Option<&[String]>,
+) -> Result<Vec<SymlinkResult>, String> {
+    let profile = config.effective_profile(profile_id)?;
+    let source = profile
+        .sources
+        .iter()
+        .find(|s| s.id == source_id)
+        .ok_or("Source not found")?;
+
+    let missing = missing_items(source, &profile.variables)
+        .into_iter()
+        .filter(|name| item_names.is_none_or(|names| names.contains(name)))
+        .collect::<Vec<_>>();
+
+    let mut results = Vec::new();
+    for item_name in missing {
+        let result = set_item_enabled(
+            config,
+            events,
+            profile_id,
+            source_id,
+            item_name.clone(),
+            false,
+        )?;
+        if result.success {
+            let source = config.source_mut(source_id).ok_or("Source not found")?;
+            source.items.retain(|item| item.name != item_name);
+        }
+        results.push(result);
     }
+    Ok(results)
 }
 
-fn remove_symlink(path: &Path) -> std::io::Result<()> {
-    #[cfg(windows)]
-    {
-        // On Windows, directory symlinks must be removed with remove_dir,
-        // and file symlinks with remove_file. Using the wrong one gives
-        // "Access is denied" (error 5).
-        //
-        // Try remove_dir first (works for directory symlinks), then fall back
-        // to remove_file (for file symlinks). This is more reliable than
-        // checking metadata.is_dir() which can be inconsistent for symlinks.
-        match std::fs::remove_dir(path) {
-            Ok(()) => Ok(()),
-            Err(e) if e.raw_os_error() == Some(145) => {
-                // ERROR_DIR_NOT_EMPTY - shouldn't happen for symlinks, but just in case
-                Err(e)
+/// Enable the items the auto-enable rules of a source pick, then remember
+/// the items in its folder. Items that fail stay disabled and are reported.
+pub(crate) fn auto_enable_items(
+    config: &mut AppConfig,
+    events: &mut Vec<AppEvent>,
+    profile_id: &str,
+    source_id: &str,
+) -> Result<Vec<SymlinkResult>, String> {
+    let profile = config.effective_profile(profile_id)?;
+    let source = profile
+        .sources
+        .iter()
+        .find(|s| s.id == source_id)
+        .ok_or("Source not found")?;
+    if source.auto_enable.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let item_names = item_names(source, &profile.variables)?;
+    let picked = item_names
+        .iter()
+        .filter(|name| !source.is_enabled(name) && source.auto_enables(name))
+        .filter(|name| !source.item(name).is_some_and(|item| item.locked))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let mut results = Vec::new();
+    for item_name in picked {
+        results.push(set_item_enabled(
+            config, events, profile_id, source_id, item_name, true,
+        )?);
+    }
+
+    let source = config.source_mut(source_id).ok_or("Source not found")?;
+    source.known_items = Some(item_names);
+    Ok(results)
+}
+
+/// Find what toggling an item would affect: the requirements enabling it
+/// needs and the enabled items it is incompatible with, or the enabled items
+/// disabling it would break.
+#[tauri::command]
+pub fn check_item_toggle(
+    state: State<'_, AppState>,
+    profile_id: String,
+    source_id: String,
+    item_name: String,
+    enabled: bool,
+) -> Result<ToggleCheck, String> {
+    let config = state.config.get()?;
+    let profile = config.effective_profile(&profile_id)?;
+    Ok(DependencyService::check(
+        &profile, &source_id, &item_name, enabled,
+    ))
+}
+
+/// Change the settings of an item. The links of an enabled item are moved to
+/// match, and the settings stay as they were if that fails.
+///
+/// Whether the item is enabled is left to `toggle_item`.
+#[tauri::command]
+pub async fn update_item(
+    state: State<'_, AppState>,
+    profile_id: String,
+    source_id: String,
+    item: ItemConfig,
+) -> Result<(), String> {
+    let item = clean_item(item)?;
+
+    let queue = state.queue.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        queue.execute(move |config, events| {
+            let mut profile = config.effective_profile(&profile_id)?;
+            let (previous_winners, previous_overridden) = LinkService::layered_links(&profile);
+
+            let source = profile
+                .sources
+                .iter_mut()
+                .find(|s| s.id == source_id)
+                .ok_or("Source not found")?;
+            let old_links = source.link_paths(&item.name, &profile.base_path, &profile.variables);
+            let item = ItemConfig {
+                enabled: source.is_enabled(&item.name),
+                ..item
+            };
+            set_item(source, item.clone());
+            let new_links = source.link_paths(&item.name, &profile.base_path, &profile.variables);
+            let (winners, overridden) = LinkService::layered_links(&profile);
+
+            let mut links = old_links;
+            for link in new_links {
+                if !links.contains(&link) {
+                    links.push(link);
+                }
             }
-            Err(_) => {
-                // Try remove_file for file symlinks
-                std::fs::remove_file(path)
+            relink_all(
+                (&previous_winners, &previous_overridden),
+                (&winners, &overridden),
+                &links,
+            )?;
+
+            // The source may belong to an included profile
+            let source = config.source_mut(&source_id).ok_or("Source not found")?;
+            set_item(source, item);
+
+            // Items of other sources may have given way to or taken over the
+            // changed links
+            let moved = previous_winners
+                .iter()
+                .chain(&previous_overridden)
+                .chain(&winners)
+                .chain(&overridden)
+                .filter(|entry| links.contains(&entry.link));
+            push_item_statuses(events, &config.effective_profile(&profile_id)?, moved);
+            Ok(())
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Trim the fields of item settings, turning empty ones into defaults.
+fn clean_item(mut item: ItemConfig) -> Result<ItemConfig, String> {
+    let clean = |value: Option<String>| {
+        value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    item.link_name = clean(item.link_name);
+    item.target_path = clean(item.target_path);
+    item.notes = clean(item.notes);
+
+    if let Some(link_name) = &item.link_name {
+        if link_name.contains(['/', '\\']) || link_name == "." || link_name == ".." {
+            return Err(format!("Invalid link name: {}", link_name));
+        }
+    }
+    Ok(item)
+}
+
+/// Replace the settings of an item, dropping them if they are the defaults.
+fn set_item(source: &mut Source, item: ItemConfig) {
+    let name = item.name.clone();
+    *source.item_mut(&name) = item;
+    source.items.retain(|item| !item.is_default());
+}
+
+/// Move the links at the given paths from the previous winners and
+/// overridden links of a profile over to the current ones, putting them all
+/// back as they were if one fails.
+pub(crate) fn relink_all(
+    previous: (&[LinkEntry], &[LinkEntry]),
+    current: (&[LinkEntry], &[LinkEntry]),
+    links: &[PathBuf],
+) -> Result<(), String> {
+    let mut done = Vec::new();
+    for link in links {
+        done.push(link);
+        if let Err(error) = relink(previous.0, current.0, current.1, link) {
+            for link in done.into_iter().rev() {
+                let _ = relink(current.0, previous.0, previous.1, link);
             }
+            return Err(error);
         }
     }
+    Ok(())
+}
 
-    #[cfg(unix)]
-    {
-        std::fs::remove_file(path)
+/// List the paths whose link points elsewhere, or no longer or only now
+/// exists, among the current winners compared to the previous ones.
+pub(crate) fn changed_links(previous: &[LinkEntry], current: &[LinkEntry]) -> Vec<PathBuf> {
+    let mut links: Vec<PathBuf> = Vec::new();
+    for entry in previous.iter().chain(current) {
+        let before = previous.iter().find(|other| other.link == entry.link);
+        let after = current.iter().find(|other| other.link == entry.link);
+        let unchanged = match (before, after) {
+            (Some(before), Some(after)) => {
+                before.source == after.source && before.target() == after.target()
+            }
+            _ => false,
+        };
+        if !unchanged && !links.contains(&entry.link) {
+            links.push(entry.link.clone());
+        }
     }
+    links
 }
 
-#[cfg(windows)]
-fn format_symlink_error(e: std::io::Error) -> String {
-    match e.raw_os_error() {
-        Some(1314) => {
-            "Permission denied: Creating symbolic links requires either:\n\
-             1. Enable Developer Mode in Windows Settings (Settings > Update & Security > For developers)\n\
-             2. Run this application as Administrator"
-                .to_string()
+/// Queue `item-status-changed` for the items of the given links, with their
+/// status in a profile as it is now. Items of sources the profile no longer
+/// has are skipped.
+pub(crate) fn push_item_statuses<'a>(
+    events: &mut Vec<AppEvent>,
+    profile: &Profile,
+    entries: impl IntoIterator<Item = &'a LinkEntry>,
+) {
+    let (winners, overridden) = LinkService::layered_links(profile);
+    let mut seen = HashSet::new();
+
+    for entry in entries {
+        if !seen.insert((&entry.source_id, &entry.item_name)) {
+            continue;
         }
-        _ => e.to_string(),
+        let Some(source) = profile.sources.iter().find(|s| s.id == entry.source_id) else {
+            continue;
+        };
+
+        let enabled = source.is_enabled(&entry.item_name);
+        events.push(AppEvent::ItemStatusChanged(ItemStatusChanged {
+            profile_id: profile.id.clone(),
+            source_id: entry.source_id.clone(),
+            item_name: entry.item_name.clone(),
+            enabled,
+            status: if enabled {
+                linked_status(&winners, &overridden, &entry.source_id, &entry.item_name)
+            } else {
+                ItemStatus::Inactive
+            },
+        }));
     }
 }
 
-#[cfg(unix)]
-fn format_symlink_error(e: std::io::Error) -> String {
-    e.to_string()
+/// Make the link at `link` point at the entry that wins it now, first
+/// removing the link of the entry that won it before if that differs.
+fn relink(
+    previous_winners: &[LinkEntry],
+    winners: &[LinkEntry],
+    overridden: &[LinkEntry],
+    link: &Path,
+) -> Result<(), String> {
+    let previous = previous_winners.iter().find(|entry| entry.link == link);
+    let next = winners.iter().find(|entry| entry.link == link);
+
+    if let Some(previous) = previous {
+        let unchanged = next.is_some_and(|next| {
+            next.source == previous.source && next.target() == previous.target()
+        });
+        if !unchanged && LinkService::points_to(&previous.link, &previous.source) {
+            LinkService::remove(link).map_err(LinkService::format_error)?;
+        }
+    }
+    LinkService::reconcile(winners, overridden, link)
 }
 
 #[tauri::command]
@@ -244,7 +744,7 @@ pub fn check_symlink_permissions() -> PermissionStatus {
                     can_create_symlinks: false,
                     requires_elevation: is_privilege_error,
                     is_developer_mode: check_developer_mode(),
-                    error_message: Some(format_symlink_error(e)),
+                    error_message: Some(LinkService::format_error(e)),
                 }
             }
         }
@@ -0,0 +1,114 @@
+use crate::commands::config::AppState;
+use crate::models::{ImportFormat, PathRemap, Profile, ProfileBundle};
+use crate::services::{Importer, ProfileBundleService, ScriptExporter};
+use std::path::Path;
+use tauri::State;
+
+/// Write the given profiles, with their sources and enabled items, to a
+/// standalone file. The profiles they include are written along with them,
+/// so the includes still resolve once imported.
+#[tauri::command]
+pub fn export_profiles(
+    state: State<'_, AppState>,
+    profile_ids: Vec<String>,
+    path: String,
+) -> Result<(), String> {
+    let profiles = {
+        let config = state.config.get()?;
+        let mut profiles: Vec<Profile> = Vec::new();
+        let mut pending = profile_ids;
+        pending.reverse();
+
+        while let Some(id) = pending.pop() {
+            if profiles.iter().any(|p| p.id == id) {
+                continue;
+            }
+            let profile = config
+                .profiles
+                .iter()
+                .find(|p| p.id == id)
+                .ok_or_else(|| format!("Profile not found: {}", id))?;
+            pending.extend(profile.includes.iter().rev().cloned());
+            profiles.push(profile.clone());
+        }
+        profiles
+    };
+
+    ProfileBundleService::write(Path::new(&path), profiles)
+}
+
+/// Write a POSIX shell script that creates, or with `uninstall` removes, the
+/// enabled links of a profile.
+#[tauri::command]
+pub fn export_profile_script(
+    state: State<'_, AppState>,
+    profile_id: String,
+    path: String,
+) -> Result<(), String> {
+    let profile = {
+        let config = state.config.get()?;
+        config.effective_profile(&profile_id)?
+    };
+
+    ScriptExporter::write(Path::new(&path), &profile)
+}
+
+/// Read an export file without importing it, so the UI can offer path
+/// remapping based on its contents.
+#[tauri::command]
+pub fn read_profile_bundle(path: String) -> Result<ProfileBundle, String> {
+    ProfileBundleService::read(Path::new(&path))
+}
+
+/// Add the profiles from an export file under new IDs, rewriting path
+/// prefixes with the given remaps.
+#[tauri::command]
+pub async fn import_profiles(
+    state: State<'_, AppState>,
+    path: String,
+    remaps: Vec<PathRemap>,
+) -> Result<Vec<Profile>, String> {
+    let bundle = ProfileBundleService::read(Path::new(&path))?;
+    let profiles = ProfileBundleService::prepare_import(bundle, &remaps);
+
+    let queue = state.queue.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        queue.execute(move |config, _| {
+            config.profiles.extend(profiles.iter().cloned());
+            Ok(profiles)
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Create a profile from a layout managed by another tool.
+///
+/// `target_path` overrides the stow target and is ignored for other formats.
+#[tauri::command]
+pub async fn import_layout(
+    state: State<'_, AppState>,
+    format: ImportFormat,
+    path: String,
+    profile_name: String,
+    target_path: Option<String>,
+) -> Result<Profile, String> {
+    let path = Path::new(&path);
+    let profile = match format {
+        ImportFormat::Stow => {
+            Importer::from_stow(path, target_path.as_deref().map(Path::new), profile_name)?
+        }
+        ImportFormat::Dotbot => Importer::from_dotbot(path, profile_name)?,
+        ImportFormat::LnScript => Importer::from_ln_script(path, profile_name)?,
+    };
+
+    let queue = state.queue.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        queue.execute(move |config, _| {
+            config.profiles.push(profile.clone());
+            Ok(profile)
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -1,11 +1,13 @@
 mod commands;
 mod models;
 mod services;
+mod utils;
 
 use commands::config::AppState;
-use models::AppConfig;
-use services::ConfigService;
-use std::sync::Mutex;
+use models::{AppConfig, ConfigIssue, IssueKind, IssueSeverity};
+use services::{
+    ConfigService, ConfigWatcher, OperationQueue, OperationRegistry, SharedConfig, SourceWatcher,
+};
 use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -31,16 +33,66 @@ pub fn run() {
 
             let config_path = app_dir.join("config.json");
 
-            // Load or create config
-            let config = if config_path.exists() {
-                ConfigService::load(&config_path).unwrap_or_default()
-            } else {
-                AppConfig::default()
+            // Load or create config. A file that can't be loaded is moved
+            // aside rather than overwritten with the defaults. If it can't be
+            // moved either, nothing is saved until it can be loaded.
+            let mut load_issues = Vec::new();
+            let mut read_only = false;
+            let config = match ConfigService::load(&config_path) {
+                Ok(config) => config,
+                Err(error) => {
+                    let message = match ConfigService::set_aside(&config_path) {
+                        Ok(aside) => format!(
+                            "{}. The file was moved to {} and default settings are used.",
+                            error,
+                            aside.display()
+                        ),
+                        Err(aside_error) => {
+                            read_only = true;
+                            format!(
+                                "{}. {}, so default settings are used and changes can't \
+                                 be saved until the file is fixed.",
+                                error, aside_error
+                            )
+                        }
+                    };
+                    load_issues.push(ConfigIssue {
+                        severity: IssueSeverity::Error,
+                        kind: IssueKind::UnreadableConfig,
+                        message,
+                        profile_id: None,
+                        source_id: None,
+                    });
+                    AppConfig::default()
+                }
             };
 
+            let shared = SharedConfig::new(config.clone());
+            let queue = OperationQueue::spawn(
+                app.handle().clone(),
+                config,
+                shared.clone(),
+                config_path.clone(),
+                read_only,
+            );
+
+            // Reload the config when it is edited by hand
+            ConfigWatcher::spawn(app.handle().clone(), config_path, queue.clone());
+            // Enable new items and drop deleted ones as their sources are
+            // set up to
+            SourceWatcher::spawn(
+                app.handle().clone(),
+                shared.clone(),
+                queue.clone(),
+                commands::symlinks::sync_source_items,
+            );
+
             app.manage(AppState {
-                config: Mutex::new(config),
-                config_path,
+                queue,
+                config: shared,
+                operations: OperationRegistry::default(),
+                snapshot_dir: app_dir.join("snapshots"),
+                load_issues,
             });
 
             Ok(())
@@ -51,18 +103,53 @@ pub fn run() {
             commands::save_config,
             commands::create_profile,
             commands::update_profile,
+            commands::duplicate_profile,
             commands::delete_profile,
             commands::create_source,
             commands::update_source,
+            commands::duplicate_source,
             commands::delete_source,
+            commands::reorder_sources,
+            commands::save_preset,
+            commands::delete_preset,
+            commands::refresh_source,
             commands::set_active_profile,
+            commands::validate_config,
+            commands::reload_config,
+            commands::resolve_config_conflict,
             // Filesystem commands
             commands::list_items,
             commands::get_items_with_status,
+            commands::find_path_owner,
             commands::validate_path,
             // Symlink commands
             commands::toggle_item,
+            commands::update_item,
+            commands::check_item_toggle,
+            commands::sync_source,
+            commands::clean_missing_items,
             commands::check_symlink_permissions,
+            // Long-running operations
+            commands::apply_profile,
+            commands::switch_profile,
+            commands::apply_preset,
+            commands::cancel_operation,
+            // Snapshot commands
+            commands::create_snapshot,
+            commands::list_snapshots,
+            commands::diff_snapshot,
+            commands::restore_snapshot,
+            commands::delete_snapshot,
+            // Target audit commands
+            commands::audit_targets,
+            commands::delete_unmanaged_links,
+            commands::adopt_unmanaged_links,
+            // Import/export commands
+            commands::export_profiles,
+            commands::export_profile_script,
+            commands::read_profile_bundle,
+            commands::import_profiles,
+            commands::import_layout,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
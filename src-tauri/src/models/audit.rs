@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+/// Why the profile doesn't account for a link in one of its target folders.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum UnmanagedKind {
+    /// Points at something that doesn't exist
+    Dangling,
+    /// Points outside the sources of the profile
+    Unknown,
+    /// Points at an item of a source, but no enabled item links it here
+    Untracked,
+    /// Points at an item of a source of another profile, which doesn't link
+    /// it here either
+    Foreign,
+}
+
+/// A symlink in a target folder of a profile that no enabled item of any
+/// profile accounts for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnmanagedLink {
+    pub link: String,
+    /// The path stored in the link
+    pub target: String,
+    pub kind: UnmanagedKind,
+    /// The source the link points into, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_name: Option<String>,
+    /// The item of that source the link points at
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_name: Option<String>,
+    /// The profile of that source, if it isn't the audited one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile_name: Option<String>,
+}
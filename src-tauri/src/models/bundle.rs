@@ -0,0 +1,32 @@
+use crate::models::Profile;
+use serde::{Deserialize, Serialize};
+
+pub const PROFILE_BUNDLE_VERSION: u32 = 2;
+
+/// Standalone file holding profiles exported for use on another machine.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileBundle {
+    pub version: u32,
+    pub profiles: Vec<Profile>,
+}
+
+/// Replaces a leading path prefix when importing a bundle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathRemap {
+    pub from: String,
+    pub to: String,
+}
+
+/// Layouts of other tools that can be imported as a profile.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportFormat {
+    /// A GNU stow directory
+    Stow,
+    /// A dotbot `install.conf.yaml`
+    Dotbot,
+    /// A shell script of `ln -s` commands
+    LnScript,
+}
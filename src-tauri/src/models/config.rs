@@ -1,7 +1,14 @@
+use crate::utils::PathExpander;
+use glob::Pattern;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Version of the config format written by this build.
+pub const CONFIG_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AppConfig {
     pub version: u32,
@@ -10,23 +17,106 @@ pub struct AppConfig {
     pub active_profile_id: Option<String>,
 }
 
+impl AppConfig {
+    /// Compose a profile with the profiles it includes, recursively.
+    ///
+    /// Included sources come first, in the order of `includes`, so the
+    /// profile's own sources take priority over them. Sources of an included
+    /// profile keep its base path, and the profile's own variables override
+    /// those of included profiles.
+    pub fn effective_profile(&self, profile_id: &str) -> Result<Profile, String> {
+        let profile = self
+            .profiles
+            .iter()
+            .find(|p| p.id == profile_id)
+            .ok_or("Profile not found")?;
+
+        let mut effective = profile.clone();
+        effective.sources.clear();
+        effective.variables.clear();
+        self.compose(profile, &mut Vec::new(), &mut effective)?;
+        Ok(effective)
+    }
+
+    fn compose<'a>(
+        &'a self,
+        profile: &'a Profile,
+        chain: &mut Vec<&'a str>,
+        effective: &mut Profile,
+    ) -> Result<(), String> {
+        if chain.contains(&profile.id.as_str()) {
+            let names = chain
+                .iter()
+                .filter_map(|id| self.profiles.iter().find(|p| p.id == *id))
+                .map(|p| p.name.as_str())
+                .chain([profile.name.as_str()])
+                .collect::<Vec<_>>();
+            return Err(format!(
+                "Profiles include each other: {}",
+                names.join(" → ")
+            ));
+        }
+        chain.push(&profile.id);
+
+        for include in &profile.includes {
+            let included = self
+                .profiles
+                .iter()
+                .find(|p| p.id == *include)
+                .ok_or_else(|| format!("Included profile not found: {}", include))?;
+            self.compose(included, chain, effective)?;
+        }
+
+        let included = chain.len() > 1;
+        for source in &profile.sources {
+            // Profiles included more than once only add their sources once
+            if effective.sources.iter().any(|s| s.id == source.id) {
+                continue;
+            }
+            let mut source = source.clone();
+            if included && source.target_path.is_none() {
+                source.target_path = Some(profile.base_path.clone());
+            }
+            effective.sources.push(source);
+        }
+        effective.variables.extend(profile.variables.clone());
+
+        chain.pop();
+        Ok(())
+    }
+
+    /// Find a source in any profile.
+    pub fn source_mut(&mut self, source_id: &str) -> Option<&mut Source> {
+        self.profiles
+            .iter_mut()
+            .flat_map(|p| p.sources.iter_mut())
+            .find(|s| s.id == source_id)
+    }
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
-            version: 1,
+            version: CONFIG_VERSION,
             profiles: Vec::new(),
             active_profile_id: None,
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Profile {
     pub id: String,
     pub name: String,
     pub base_path: String,
     pub sources: Vec<Source>,
+    /// Variables available to `$NAME` / `${NAME}` in this profile's paths
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, String>,
+    /// IDs of profiles whose sources are part of this one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub includes: Vec<String>,
 }
 
 impl Profile {
@@ -36,11 +126,13 @@ impl Profile {
             name,
             base_path,
             sources: Vec::new(),
+            variables: BTreeMap::new(),
+            includes: Vec::new(),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Source {
     pub id: String,
@@ -48,8 +140,215 @@ pub struct Source {
     pub source_path: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub target_path: Option<String>,
+    /// The target last taken from the source's manifest, so a change to it
+    /// reaches the source unless the source was given a target of its own
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifest_target: Option<String>,
+    /// Further folders every enabled item is linked into as well
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub additional_targets: Vec<String>,
+    /// Settings of the items that are enabled or configured
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<ItemConfig>,
+    /// Glob patterns for items that are never listed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<String>,
+    /// Link names that differ from the item name, keyed by item name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub link_names: BTreeMap<String, String>,
+    /// List items whose name starts with a dot
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub include_hidden: bool,
+    /// Machines this source applies to; applies everywhere when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<Condition>,
+    /// Machines individual items apply to, keyed by item name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub item_conditions: BTreeMap<String, Condition>,
+    /// Saved sets of enabled items, keyed by preset name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub presets: BTreeMap<String, Vec<String>>,
+    /// Rules enabling items without toggling them by hand
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub auto_enable: Vec<AutoEnableRule>,
+    /// Items found in the source folder when the rules last ran, so that
+    /// only items added later count as new
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub known_items: Option<Vec<String>>,
+    /// What happens to enabled items that disappear from the folder
+    #[serde(default, skip_serializing_if = "MissingItemPolicy::is_ask")]
+    pub missing_items: MissingItemPolicy,
+    /// How the enabled items look on disk, keyed by item name, to follow
+    /// them when they are renamed
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub identities: BTreeMap<String, FileIdentity>,
+}
+
+/// The settings of a profile edited in its form. They are merged into the
+/// profile as it is by the time the edit is applied, so changes made to it
+/// meanwhile, such as toggled items, are kept.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilePatch {
+    pub name: String,
+    pub base_path: String,
+    pub includes: Vec<String>,
+}
+
+/// The settings of a source edited in its form, merged like a
+/// [`ProfilePatch`].
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourcePatch {
+    pub name: String,
+    pub source_path: String,
+    pub target_path: Option<String>,
+    pub additional_targets: Vec<String>,
+    pub auto_enable: Vec<AutoEnableRule>,
+    pub missing_items: MissingItemPolicy,
+}
+
+/// Picks items of a source to enable when they appear in its folder.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum AutoEnableRule {
+    /// Every new item
+    NewItems,
+    /// New items whose name matches a glob pattern, e.g. `*.esp`
+    Matching { pattern: String },
+    /// Every item in the folder, including those disabled before
+    Mirror,
+}
+
+/// Recognizes an item's file or folder after it is renamed: by its content,
+/// or by its inode if it is still the same kind of entry of the same size.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileIdentity {
+    /// Device and inode number, on systems that have them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inode: Option<String>,
+    /// Hash of the start of a file, or of the names and sizes in a folder
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub directory: bool,
+    /// Size of a file in bytes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+}
+
+/// What happens to the enabled items of a source that are no longer in its
+/// folder.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MissingItemPolicy {
+    /// List them so their links can be removed by hand
+    #[default]
+    Ask,
+    /// Remove their links and settings whenever the source is refreshed or
+    /// its folder changes
+    Remove,
+}
+
+impl MissingItemPolicy {
+    pub fn is_ask(&self) -> bool {
+        *self == Self::Ask
+    }
+}
+
+/// Settings of a single item of a source. Items without one are disabled
+/// and use the source's settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemConfig {
+    pub name: String,
     #[serde(default)]
-    pub enabled_items: Vec<String>,
+    pub enabled: bool,
+    /// Name of the link, taking precedence over the source's `link_names`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link_name: Option<String>,
+    /// Folder to link into instead of the source's target folders
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_path: Option<String>,
+    #[serde(default, skip_serializing_if = "LinkMode::is_absolute")]
+    pub link_mode: LinkMode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// Locked items keep their state until they are unlocked
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub locked: bool,
+    /// Items this one needs to work
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requires: Vec<ItemRef>,
+    /// Items that can't be enabled together with this one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conflicts: Vec<ItemRef>,
+}
+
+impl ItemConfig {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            enabled: false,
+            link_name: None,
+            target_path: None,
+            link_mode: LinkMode::Absolute,
+            notes: None,
+            locked: false,
+            requires: Vec::new(),
+            conflicts: Vec::new(),
+        }
+    }
+
+    /// Check whether this only holds the defaults of a disabled item.
+    pub fn is_default(&self) -> bool {
+        *self == Self::new(self.name.clone())
+    }
+}
+
+/// Refers to an item of the same source, or of another source of the
+/// profile when `source_id` is given.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemRef {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_id: Option<String>,
+    pub name: String,
+}
+
+/// How a link refers to its item.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LinkMode {
+    /// The link holds the full path of the item
+    #[default]
+    Absolute,
+    /// The link holds the path from its folder to the item, so both can be
+    /// moved together
+    Relative,
+}
+
+impl LinkMode {
+    pub fn is_absolute(&self) -> bool {
+        *self == Self::Absolute
+    }
+}
+
+/// Restricts a source or item to matching machines. Every field that is set
+/// has to match.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Condition {
+    /// Operating systems (`linux`, `macos`, `windows`) or families (`unix`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub os: Vec<String>,
+    /// Glob pattern for the hostname, matched case-insensitively
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    /// Environment variables that have to be set
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env: Vec<String>,
 }
 
 impl Source {
@@ -59,12 +358,220 @@ impl Source {
             name,
             source_path,
             target_path,
-            enabled_items: Vec::new(),
+            manifest_target: None,
+            additional_targets: Vec::new(),
+            items: Vec::new(),
+            ignore: Vec::new(),
+            link_names: BTreeMap::new(),
+            include_hidden: false,
+            condition: None,
+            item_conditions: BTreeMap::new(),
+            presets: BTreeMap::new(),
+            auto_enable: Vec::new(),
+            known_items: None,
+            missing_items: MissingItemPolicy::Ask,
+            identities: BTreeMap::new(),
+        }
+    }
+
+    /// Get the effective target path (own or profile default), expanded
+    pub fn get_target_path(
+        &self,
+        profile_base_path: &str,
+        variables: &BTreeMap<String, String>,
+    ) -> PathBuf {
+        let raw = self.target_path.as_deref().unwrap_or(profile_base_path);
+        PathExpander::expand(raw, variables)
+    }
+
+    /// Get every expanded target path, the effective one first
+    pub fn get_target_paths(
+        &self,
+        profile_base_path: &str,
+        variables: &BTreeMap<String, String>,
+    ) -> Vec<PathBuf> {
+        let mut paths = vec![self.get_target_path(profile_base_path, variables)];
+        for target in &self.additional_targets {
+            let path = PathExpander::expand(target, variables);
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
         }
+        paths
+    }
+
+    /// Get the expanded source path
+    pub fn get_source_path(&self, variables: &BTreeMap<String, String>) -> PathBuf {
+        PathExpander::expand(&self.source_path, variables)
+    }
+
+    /// Get the name of the link created for an item
+    pub fn link_name<'a>(&'a self, item_name: &'a str) -> &'a str {
+        self.item(item_name)
+            .and_then(|item| item.link_name.as_deref())
+            .or_else(|| self.link_names.get(item_name).map(String::as_str))
+            .unwrap_or(item_name)
+    }
+
+    /// Get the expanded paths of the links for an item, one per target
+    /// folder unless the item has its own
+    pub fn link_paths(
+        &self,
+        item_name: &str,
+        profile_base_path: &str,
+        variables: &BTreeMap<String, String>,
+    ) -> Vec<PathBuf> {
+        let target_paths = match self.item(item_name).and_then(|i| i.target_path.as_deref()) {
+            Some(target_path) => vec![PathExpander::expand(target_path, variables)],
+            None => self.get_target_paths(profile_base_path, variables),
+        };
+        let link_name = self.link_name(item_name);
+
+        target_paths
+            .into_iter()
+            .map(|target_path| target_path.join(link_name))
+            .collect()
+    }
+
+    /// Check whether the auto-enable rules pick an item. Only `Mirror` picks
+    /// items the source has seen before.
+    pub fn auto_enables(&self, item_name: &str) -> bool {
+        let is_new = self
+            .known_items
+            .as_ref()
+            .is_some_and(|known| !known.iter().any(|name| name == item_name));
+
+        self.auto_enable.iter().any(|rule| match rule {
+            AutoEnableRule::NewItems => is_new,
+            AutoEnableRule::Matching { pattern } => {
+                is_new && Pattern::new(pattern).is_ok_and(|p| p.matches(item_name))
+            }
+            AutoEnableRule::Mirror => true,
+        })
+    }
+
+    /// Get the settings of an item, if it has any
+    pub fn item(&self, item_name: &str) -> Option<&ItemConfig> {
+        self.items.iter().find(|item| item.name == item_name)
+    }
+
+    /// Get the settings of an item, adding them if it has none
+    pub fn item_mut(&mut self, item_name: &str) -> &mut ItemConfig {
+        let index = match self.items.iter().position(|item| item.name == item_name) {
+            Some(index) => index,
+            None => {
+                self.items.push(ItemConfig::new(item_name.to_string()));
+                self.items.len() - 1
+            }
+        };
+        &mut self.items[index]
+    }
+
+    pub fn is_enabled(&self, item_name: &str) -> bool {
+        self.item(item_name).is_some_and(|item| item.enabled)
+    }
+
+    /// Get the names of the enabled items
+    pub fn enabled_items(&self) -> Vec<String> {
+        self.items
+            .iter()
+            .filter(|item| item.enabled)
+            .map(|item| item.name.clone())
+            .collect()
+    }
+
+    /// Enable or disable an item, dropping settings that are left at their
+    /// defaults
+    pub fn set_enabled(&mut self, item_name: &str, enabled: bool) {
+        self.item_mut(item_name).enabled = enabled;
+        self.items.retain(|item| !item.is_default());
+    }
+
+    /// Point requirements and incompatibilities at the new IDs of sources
+    /// that were given fresh ones, e.g. when copying them.
+    pub fn remap_item_refs(&mut self, new_ids: &HashMap<String, String>) {
+        let references = self
+            .items
+            .iter_mut()
+            .flat_map(|item| item.requires.iter_mut().chain(&mut item.conflicts));
+        for reference in references {
+            if let Some(id) = reference.source_id.as_ref().and_then(|id| new_ids.get(id)) {
+                reference.source_id = Some(id.clone());
+            }
+        }
+    }
+
+    /// Move everything the source keeps about an item to a new name, after
+    /// the item was renamed in the source folder.
+    pub fn rename_item(&mut self, old_name: &str, new_name: &str) {
+        let rename = |name: &mut String| {
+            if name == old_name {
+                *name = new_name.to_string();
+            }
+        };
+
+        for item in &mut self.items {
+            rename(&mut item.name);
+        }
+        self.presets.values_mut().flatten().for_each(rename);
+        self.known_items.iter_mut().flatten().for_each(rename);
+        if let Some(link_name) = self.link_names.remove(old_name) {
+            self.link_names.insert(new_name.to_string(), link_name);
+        }
+        if let Some(condition) = self.item_conditions.remove(old_name) {
+            self.item_conditions.insert(new_name.to_string(), condition);
+        }
+        if let Some(identity) = self.identities.remove(old_name) {
+            self.identities.insert(new_name.to_string(), identity);
+        }
+
+        let id = self.id.clone();
+        self.rename_item_refs(&id, old_name, new_name);
+    }
+
+    /// Point requirements and incompatibilities at the new name of an item
+    /// of the source with ID `source_id`.
+    pub fn rename_item_refs(&mut self, source_id: &str, old_name: &str, new_name: &str) {
+        let own = self.id == source_id;
+        let references = self
+            .items
+            .iter_mut()
+            .flat_map(|item| item.requires.iter_mut().chain(&mut item.conflicts));
+        for reference in references {
+            let same_source = match &reference.source_id {
+                Some(id) => id == source_id,
+                None => own,
+            };
+            if same_source && reference.name == old_name {
+                reference.name = new_name.to_string();
+            }
+        }
+    }
+
+    /// Enable exactly the given items, keeping the other settings of all
+    pub fn set_enabled_items(&mut self, item_names: &[String]) {
+        for item in &mut self.items {
+            item.enabled = item_names.contains(&item.name);
+        }
+        for item_name in item_names {
+            self.item_mut(item_name).enabled = true;
+        }
+        self.items.retain(|item| !item.is_default());
+    }
+
+    /// Get the conditions an item has to meet, its source's first
+    pub fn conditions<'a>(&'a self, item_name: &str) -> impl Iterator<Item = &'a Condition> {
+        self.condition
+            .iter()
+            .chain(self.item_conditions.get(item_name))
     }
 
-    /// Get the effective target path (own or profile default)
-    pub fn get_target_path<'a>(&'a self, profile_base_path: &'a str) -> &'a str {
-        self.target_path.as_deref().unwrap_or(profile_base_path)
+    /// Check whether an item matches one of the ignore patterns
+    pub fn is_ignored(&self, item_name: &str) -> bool {
+        self.ignore.iter().any(|pattern| {
+            Pattern::new(pattern)
+                .map(|p| p.matches(item_name))
+                .unwrap_or(false)
+        })
     }
 }
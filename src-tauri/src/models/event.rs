@@ -0,0 +1,61 @@
+use crate::models::{AppConfig, ConfigIssue, ItemStatus};
+use serde::{Deserialize, Serialize};
+
+/// Payload of the `config-changed` event, with the problems found by
+/// validating the new config.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigChanged {
+    pub config: AppConfig,
+    pub issues: Vec<ConfigIssue>,
+}
+
+/// Payload of the `profile-applied` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileApplied {
+    pub profile_id: String,
+    pub operation_id: String,
+}
+
+/// Payload of the `item-status-changed` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemStatusChanged {
+    pub profile_id: String,
+    pub source_id: String,
+    pub item_name: String,
+    pub enabled: bool,
+    pub status: ItemStatus,
+}
+
+/// Payload of the `config-conflict` event, raised when the config file was
+/// edited on disk while the app held changes it could not save.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigConflict {
+    pub disk_config: AppConfig,
+}
+
+/// Payload of the `config-reload-failed` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigReloadFailed {
+    pub error: String,
+}
+
+/// Payload of the `source-watch-failed` event, raised when changes to source
+/// folders are no longer followed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceWatchFailed {
+    pub error: String,
+}
+
+/// Payload of the `config-save-failed` event, raised when the changes of an
+/// operation are kept in memory but could not be written to the file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigSaveFailed {
+    pub error: String,
+}
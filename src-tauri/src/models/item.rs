@@ -1,3 +1,4 @@
+use crate::models::{ItemConfig, Source};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -7,6 +8,13 @@ pub enum ItemStatus {
     Inactive,
     Broken,
     Conflict,
+    /// The item's conditions don't match this machine
+    #[serde(rename = "notApplicable")]
+    NotApplicable,
+    /// A source with a higher priority links the same name
+    Overridden,
+    /// Enabled, but no longer in the source folder
+    Missing,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +27,83 @@ pub struct Item {
     pub enabled: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub conflict_source: Option<String>,
+    /// The source whose item is linked instead of this one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overridden_by: Option<String>,
+    /// Alternates of this item, such as `name##os.linux`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternates: Vec<String>,
+    /// The alternate linked on this machine
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alternate: Option<String>,
+    /// Status per target folder, for sources linking into more than one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<ItemTarget>,
+    /// The item's settings in its source, if it has any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settings: Option<ItemConfig>,
+}
+
+/// The status of an item's link in one target folder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemTarget {
+    pub path: String,
+    pub status: ItemStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conflict_source: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overridden_by: Option<String>,
+}
+
+/// An item that toggling another one depends on or affects.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RelatedItem {
+    pub source_id: String,
+    pub source_name: String,
+    pub item_name: String,
+}
+
+/// What toggling an item would affect, so it can be confirmed first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToggleCheck {
+    /// Items enabling it needs that aren't enabled yet
+    pub missing_requirements: Vec<RelatedItem>,
+    /// Enabled items it can't be enabled together with
+    pub conflicts: Vec<RelatedItem>,
+    /// Enabled items that need it, which disabling it would break
+    pub dependents: Vec<RelatedItem>,
+}
+
+/// How a looked up path belongs to an item.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum PathRole {
+    /// The path is one of the item's links, or lies in a linked folder
+    Link,
+    /// The path is the item in its source folder, or lies in it
+    Source,
+}
+
+/// An item a looked up path belongs to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathOwner {
+    pub profile_id: String,
+    pub profile_name: String,
+    /// Whether the profile is the active one
+    pub active: bool,
+    pub source_id: String,
+    pub source_name: String,
+    pub item_name: String,
+    pub role: PathRole,
+    /// The item's link the path is or lies in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+    pub enabled: bool,
+    pub status: ItemStatus,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,3 +124,10 @@ pub struct SymlinkResult {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceRefresh {
+    pub source: Source,
+    pub results: Vec<SymlinkResult>,
+}
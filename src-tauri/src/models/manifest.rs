@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Link layout declared by a `.symlinks.toml` file inside a source directory.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SourceManifest {
+    /// Default target directory, relative paths are resolved against the
+    /// source directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Glob patterns for items that should never be listed
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    /// Link names that differ from the item name, keyed by item name
+    #[serde(default)]
+    pub names: BTreeMap<String, String>,
+    /// Items that should be linked
+    #[serde(default)]
+    pub enabled: Vec<String>,
+}
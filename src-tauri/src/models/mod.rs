@@ -1,5 +1,30 @@
+pub mod audit;
+pub mod bundle;
 pub mod config;
+pub mod event;
 pub mod item;
+pub mod manifest;
+pub mod operation;
+pub mod snapshot;
+pub mod validation;
 
-pub use config::{AppConfig, Profile, Source};
-pub use item::{Item, ItemStatus, PermissionStatus, SymlinkResult};
+pub use audit::{UnmanagedKind, UnmanagedLink};
+pub use bundle::{ImportFormat, PathRemap, ProfileBundle, PROFILE_BUNDLE_VERSION};
+pub use config::{
+    AppConfig, AutoEnableRule, Condition, FileIdentity, ItemConfig, ItemRef, LinkMode,
+    MissingItemPolicy, Profile, ProfilePatch, Source, SourcePatch, CONFIG_VERSION,
+};
+pub use event::{
+    ConfigChanged, ConfigConflict, ConfigReloadFailed, ConfigSaveFailed, ItemStatusChanged,
+    ProfileApplied, SourceWatchFailed,
+};
+pub use item::{
+    Item, ItemStatus, ItemTarget, PathOwner, PathRole, PermissionStatus, RelatedItem,
+    SourceRefresh, SymlinkResult, ToggleCheck,
+};
+pub use manifest::SourceManifest;
+pub use operation::{OperationProgress, OperationReport};
+pub use snapshot::{
+    Snapshot, SnapshotChange, SnapshotDiff, SnapshotItemDiff, SnapshotLink, SnapshotSummary,
+};
+pub use validation::{ConfigIssue, IssueKind, IssueSeverity};
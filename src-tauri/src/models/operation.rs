@@ -0,0 +1,22 @@
+use crate::models::SymlinkResult;
+use serde::{Deserialize, Serialize};
+
+/// Payload of the `operation-progress` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationProgress {
+    pub operation_id: String,
+    pub done: usize,
+    pub total: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_path: Option<String>,
+}
+
+/// Outcome of a long-running link operation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationReport {
+    pub operation_id: String,
+    pub cancelled: bool,
+    pub results: Vec<SymlinkResult>,
+}
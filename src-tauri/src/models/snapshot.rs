@@ -0,0 +1,80 @@
+use crate::models::Profile;
+use serde::{Deserialize, Serialize};
+
+/// A profile as it was at one point in time, together with the links it
+/// had on disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Milliseconds since the Unix epoch
+    pub created_at: u64,
+    pub profile: Profile,
+    pub links: Vec<SnapshotLink>,
+}
+
+/// A link that existed on disk when the snapshot was taken.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotLink {
+    pub source_id: String,
+    pub item_name: String,
+    pub link: String,
+    pub target: String,
+}
+
+/// The parts of a snapshot shown when listing them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotSummary {
+    pub id: String,
+    pub profile_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub created_at: u64,
+    pub link_count: usize,
+}
+
+impl From<&Snapshot> for SnapshotSummary {
+    fn from(snapshot: &Snapshot) -> Self {
+        Self {
+            id: snapshot.id.clone(),
+            profile_id: snapshot.profile.id.clone(),
+            label: snapshot.label.clone(),
+            created_at: snapshot.created_at,
+            link_count: snapshot.links.len(),
+        }
+    }
+}
+
+/// What restoring a snapshot would change for an item.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum SnapshotChange {
+    /// Enabled in the snapshot but not now
+    Enable,
+    /// Enabled now but not in the snapshot
+    Disable,
+    /// Enabled in both, but its link on disk differs from the snapshot
+    Relink,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotItemDiff {
+    pub source_id: String,
+    pub source_name: String,
+    pub item_name: String,
+    pub change: SnapshotChange,
+}
+
+/// Differences between a snapshot and the current state of its profile.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotDiff {
+    /// Whether the profile's settings, such as paths or sources, changed
+    pub profile_changed: bool,
+    pub items: Vec<SnapshotItemDiff>,
+}
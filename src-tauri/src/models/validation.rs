@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueSeverity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum IssueKind {
+    DuplicateProfileId,
+    DuplicateSourceId,
+    MissingActiveProfile,
+    TargetInsideSource,
+    DuplicateSource,
+    MissingPath,
+    UndefinedVariable,
+    DuplicateEnabledItem,
+    InvalidCondition,
+    InvalidInclude,
+    InvalidItemReference,
+    InvalidAutoEnableRule,
+    UnreadableConfig,
+}
+
+/// A problem found in the config, pointing at the profile/source it affects.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigIssue {
+    pub severity: IssueSeverity,
+    pub kind: IssueKind,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_id: Option<String>,
+}
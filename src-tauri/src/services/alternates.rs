@@ -0,0 +1,268 @@
+use crate::services::Host;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Separates an item name from the conditions of an alternate.
+pub const ALTERNATE_SEPARATOR: &str = "##";
+
+/// A single condition in the suffix of an alternate, e.g. `os.linux`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlternateLabel<'a> {
+    Default,
+    Arch(&'a str),
+    Os(&'a str),
+    Hostname(&'a str),
+    User(&'a str),
+    /// Only there to keep an editor's file type, always matches
+    Extension,
+    /// Not supported here, never matches
+    Unsupported,
+}
+
+/// The yadm-style alternates of the items in a source directory, such as
+/// `alacritty.toml##hostname.work` and `alacritty.toml##os.linux`.
+pub struct AlternateIndex {
+    source_dir: PathBuf,
+    variants: BTreeMap<String, Vec<String>>,
+}
+
+impl AlternateIndex {
+    pub fn load(source_dir: &Path) -> Self {
+        let mut variants: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+        for entry in std::fs::read_dir(source_dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+        {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if let Some((item_name, _)) = Self::split(&file_name) {
+                variants
+                    .entry(item_name.to_string())
+                    .or_default()
+                    .push(file_name);
+            }
+        }
+        for names in variants.values_mut() {
+            names.sort();
+        }
+
+        Self {
+            source_dir: source_dir.to_path_buf(),
+            variants,
+        }
+    }
+
+    /// Get the alternates of an item.
+    pub fn variants(&self, item_name: &str) -> &[String] {
+        self.variants
+            .get(item_name)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Check whether an item is still in the source directory, as itself or
+    /// as one of its alternates.
+    pub fn contains(&self, item_name: &str) -> bool {
+        let plain = self.source_dir.join(item_name);
+        plain.exists() || plain.is_symlink() || !self.variants(item_name).is_empty()
+    }
+
+    /// Get the file to link for an item on this machine. Returns `None` if
+    /// the item only has alternates and none of them matches.
+    pub fn resolve(&self, item_name: &str, host: &Host) -> Option<String> {
+        let plain = self.source_dir.join(item_name);
+        let plain_exists = plain.exists() || plain.is_symlink();
+        Self::pick(item_name, self.variants(item_name), plain_exists, host)
+    }
+
+    /// Pick the best matching alternate, falling back to the item itself.
+    pub fn pick(
+        item_name: &str,
+        variants: &[String],
+        plain_exists: bool,
+        host: &Host,
+    ) -> Option<String> {
+        if variants.is_empty() {
+            return Some(item_name.to_string());
+        }
+
+        let best = variants
+            .iter()
+            .filter_map(|variant| {
+                let (_, conditions) = Self::split(variant)?;
+                Some((Self::score(conditions, host)?, variant))
+            })
+            // On equal scores the first name wins, for a stable choice
+            .min_by_key(|(score, _)| std::cmp::Reverse(*score))
+            .map(|(_, variant)| variant.clone());
+
+        best.or_else(|| plain_exists.then(|| item_name.to_string()))
+    }
+
+    /// Split a file name into the item it's an alternate of and its
+    /// conditions. Returns `None` for names without a suffix.
+    pub fn split(file_name: &str) -> Option<(&str, &str)> {
+        file_name
+            .split_once(ALTERNATE_SEPARATOR)
+            .filter(|(item_name, conditions)| !item_name.is_empty() && !conditions.is_empty())
+    }
+
+    /// Parse the comma separated conditions of an alternate.
+    pub fn labels(conditions: &str) -> Vec<AlternateLabel<'_>> {
+        conditions
+            .split(',')
+            .map(|label| match label.split_once('.') {
+                None if label == "default" => AlternateLabel::Default,
+                Some(("a" | "arch", value)) => AlternateLabel::Arch(value),
+                Some(("o" | "os", value)) => AlternateLabel::Os(value),
+                Some(("h" | "hostname", value)) => AlternateLabel::Hostname(value),
+                Some(("u" | "user", value)) => AlternateLabel::User(value),
+                Some(("e" | "extension", _)) => AlternateLabel::Extension,
+                _ => AlternateLabel::Unsupported,
+            })
+            .collect()
+    }
+
+    /// Score how specific a set of labels is. Alternates with a higher
+    /// score win when several match.
+    pub fn specificity(labels: &[AlternateLabel]) -> u32 {
+        labels
+            .iter()
+            .map(|label| match label {
+                AlternateLabel::Arch(_) => 1,
+                AlternateLabel::Os(_) => 2,
+                AlternateLabel::Hostname(_) => 4,
+                AlternateLabel::User(_) => 8,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// Score the conditions of an alternate for this machine, or `None` if
+    /// one of them doesn't match.
+    fn score(conditions: &str, host: &Host) -> Option<u32> {
+        let labels = Self::labels(conditions);
+        labels
+            .iter()
+            .all(|label| Self::matches(label, host))
+            .then(|| Self::specificity(&labels))
+    }
+
+    fn matches(label: &AlternateLabel, host: &Host) -> bool {
+        match *label {
+            AlternateLabel::Default | AlternateLabel::Extension => true,
+            AlternateLabel::Arch(arch) => arch.eq_ignore_ascii_case(host.arch),
+            AlternateLabel::Os(os) => host.is_os(os),
+            AlternateLabel::Hostname(hostname) => {
+                let short_hostname = host.hostname.split('.').next().unwrap_or_default();
+                hostname.eq_ignore_ascii_case(&host.hostname)
+                    || hostname.eq_ignore_ascii_case(short_hostname)
+            }
+            AlternateLabel::User(user) => user == host.user,
+            AlternateLabel::Unsupported => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host() -> Host {
+        Host {
+            os: "linux",
+            family: "unix",
+            arch: "x86_64",
+            hostname: "work-laptop.example.com".to_string(),
+            user: "alice".to_string(),
+        }
+    }
+
+    fn variants(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn splits_names_at_the_first_separator() {
+        assert_eq!(
+            AlternateIndex::split("alacritty.toml##os.linux,h.work"),
+            Some(("alacritty.toml", "os.linux,h.work"))
+        );
+        assert_eq!(AlternateIndex::split("alacritty.toml"), None);
+        assert_eq!(AlternateIndex::split("##os.linux"), None);
+        assert_eq!(AlternateIndex::split("alacritty.toml##"), None);
+    }
+
+    #[test]
+    fn parses_short_and_long_labels() {
+        assert_eq!(
+            AlternateIndex::labels("default,a.arm64,o.Linux,hostname.work,u.alice,e.toml"),
+            vec![
+                AlternateLabel::Default,
+                AlternateLabel::Arch("arm64"),
+                AlternateLabel::Os("Linux"),
+                AlternateLabel::Hostname("work"),
+                AlternateLabel::User("alice"),
+                AlternateLabel::Extension,
+            ]
+        );
+        assert_eq!(
+            AlternateIndex::labels("class.work,template,os"),
+            vec![AlternateLabel::Unsupported; 3]
+        );
+    }
+
+    #[test]
+    fn picks_the_most_specific_match() {
+        let host = host();
+        let variants = variants(&[
+            "gitconfig##default",
+            "gitconfig##os.linux",
+            "gitconfig##os.linux,a.x86_64",
+            "gitconfig##h.work-laptop",
+            "gitconfig##u.bob",
+        ]);
+
+        assert_eq!(
+            AlternateIndex::pick("gitconfig", &variants, true, &host),
+            Some("gitconfig##h.work-laptop".to_string())
+        );
+        assert_eq!(
+            AlternateIndex::pick("gitconfig", &variants[..3], true, &host),
+            Some("gitconfig##os.linux,a.x86_64".to_string())
+        );
+    }
+
+    #[test]
+    fn falls_back_to_the_plain_item() {
+        let host = host();
+        let variants = variants(&["vimrc##os.macos", "vimrc##class.work"]);
+
+        assert_eq!(
+            AlternateIndex::pick("vimrc", &variants, true, &host),
+            Some("vimrc".to_string())
+        );
+        assert_eq!(AlternateIndex::pick("vimrc", &variants, false, &host), None);
+        assert_eq!(
+            AlternateIndex::pick("vimrc", &[], false, &host),
+            Some("vimrc".to_string())
+        );
+    }
+
+    #[test]
+    fn matches_full_and_short_hostnames() {
+        let host = host();
+
+        for variant in ["zshrc##h.work-laptop", "zshrc##h.WORK-LAPTOP.example.com"] {
+            assert_eq!(
+                AlternateIndex::pick("zshrc", &variants(&[variant]), false, &host),
+                Some(variant.to_string())
+            );
+        }
+        assert_eq!(
+            AlternateIndex::pick("zshrc", &variants(&["zshrc##h.work"]), false, &host),
+            None
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use crate::models::CONFIG_VERSION;
+use serde_json::{json, Value};
+
+/// Upgrades config, snapshot and bundle files written by older versions
+/// before they are parsed.
+pub struct ConfigMigration;
+
+impl ConfigMigration {
+    /// Get the format version of a config file. Files from before versioning
+    /// count as version 1.
+    pub fn version(config: &Value) -> u32 {
+        config
+            .get("version")
+            .and_then(Value::as_u64)
+            .map_or(1, |version| version as u32)
+    }
+
+    /// Upgrade a config file to the current version. Fails for files written
+    /// by a newer version, which this one can't read without losing data.
+    pub fn migrate_config(config: &mut Value) -> Result<(), String> {
+        let version = Self::version(config);
+        if version > CONFIG_VERSION {
+            return Err(format!(
+                "Config file version {} is newer than supported version {}",
+                version, CONFIG_VERSION
+            ));
+        }
+
+        for profile in array_mut(config, "profiles") {
+            Self::migrate_profile(profile);
+        }
+        if let Some(config) = config.as_object_mut() {
+            config.insert("version".to_string(), json!(CONFIG_VERSION));
+        }
+        Ok(())
+    }
+
+    /// Upgrade a single profile. Profiles already in the current format are
+    /// left as they are.
+    pub fn migrate_profile(profile: &mut Value) {
+        for source in array_mut(profile, "sources") {
+            let Some(source) = source.as_object_mut() else {
+                continue;
+            };
+
+            // Version 2: the names of enabled items became item records
+            let Some(enabled_items) = source.remove("enabledItems") else {
+                continue;
+            };
+            if source.contains_key("items") {
+                continue;
+            }
+            let items = enabled_items
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(|name| json!({ "name": name, "enabled": true }))
+                .collect::<Vec<_>>();
+            source.insert("items".to_string(), Value::Array(items));
+        }
+    }
+}
+
+fn array_mut<'a>(value: &'a mut Value, key: &str) -> impl Iterator<Item = &'a mut Value> {
+    value
+        .get_mut(key)
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unversioned_files_are_version_1() {
+        assert_eq!(ConfigMigration::version(&json!({ "profiles": [] })), 1);
+        assert_eq!(ConfigMigration::version(&json!({ "version": 2 })), 2);
+    }
+
+    #[test]
+    fn migrates_enabled_items_to_item_records() {
+        let mut config = json!({
+            "profiles": [{
+                "sources": [
+                    { "enabledItems": ["nvim", "zshrc"] },
+                    { "enabledItems": ["old"], "items": [{ "name": "kept", "enabled": true }] }
+                ]
+            }]
+        });
+
+        ConfigMigration::migrate_config(&mut config).unwrap();
+
+        assert_eq!(config["version"], json!(CONFIG_VERSION));
+        assert_eq!(
+            config["profiles"][0]["sources"][0],
+            json!({
+                "items": [
+                    { "name": "nvim", "enabled": true },
+                    { "name": "zshrc", "enabled": true }
+                ]
+            })
+        );
+        // Item records win over a leftover list
+        assert_eq!(
+            config["profiles"][0]["sources"][1],
+            json!({ "items": [{ "name": "kept", "enabled": true }] })
+        );
+    }
+
+    #[test]
+    fn leaves_current_files_unchanged() {
+        let mut config = json!({
+            "version": CONFIG_VERSION,
+            "profiles": [{ "sources": [{ "items": [{ "name": "nvim", "enabled": true }] }] }]
+        });
+        let original = config.clone();
+
+        ConfigMigration::migrate_config(&mut config).unwrap();
+
+        assert_eq!(config, original);
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let mut config = json!({
+            "version": CONFIG_VERSION + 1,
+            "profiles": [{ "sources": [{ "enabledItems": ["nvim"] }] }]
+        });
+        let original = config.clone();
+
+        assert!(ConfigMigration::migrate_config(&mut config).is_err());
+        assert_eq!(config, original);
+    }
+}
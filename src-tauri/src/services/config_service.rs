@@ -1,6 +1,9 @@
 use crate::models::AppConfig;
+use crate::services::ConfigMigration;
+use serde_json::Value;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 pub struct ConfigService;
 
@@ -13,8 +16,33 @@ impl ConfigService {
         let content = fs::read_to_string(path)
             .map_err(|e| format!("Failed to read config file: {}", e))?;
 
-        serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse config file: {}", e))
+        let mut value: Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse config file: {}", e))?;
+
+        // Keep a copy of a file from an older version before it is replaced
+        let version = ConfigMigration::version(&value);
+        ConfigMigration::migrate_config(&mut value)?;
+        if version < ConfigMigration::version(&value) {
+            let backup = path.with_extension(format!("json.v{}.bak", version));
+            if !backup.exists() {
+                fs::write(&backup, &content)
+                    .map_err(|e| format!("Failed to back up config file: {}", e))?;
+            }
+        }
+
+        serde_json::from_value(value).map_err(|e| format!("Failed to parse config file: {}", e))
+    }
+
+    /// Move a config file that can't be loaded out of the way, so the
+    /// defaults used instead don't overwrite it. Returns its new path.
+    pub fn set_aside(path: &Path) -> Result<PathBuf, String> {
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        let aside = path.with_extension(format!("json.unreadable-{}", stamp));
+        fs::rename(path, &aside).map_err(|e| format!("Failed to move config file aside: {}", e))?;
+        Ok(aside)
     }
 
     pub fn save(path: &Path, config: &AppConfig) -> Result<(), String> {
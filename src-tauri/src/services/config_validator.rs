@@ -0,0 +1,541 @@
+use crate::models::{
+    AppConfig, AutoEnableRule, Condition, ConfigIssue, IssueKind, IssueSeverity, Profile, Source,
+};
+use crate::services::host::KNOWN_OS_NAMES;
+use crate::utils::PathExpander;
+use glob::Pattern;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+/// Checks a config for problems that would otherwise only surface later,
+/// when links are created.
+pub struct ConfigValidator {
+    issues: Vec<ConfigIssue>,
+}
+
+impl ConfigValidator {
+    pub fn validate(config: &AppConfig) -> Vec<ConfigIssue> {
+        let mut validator = Self { issues: Vec::new() };
+        validator.check_ids(config);
+        validator.check_active_profile(config);
+        validator.check_includes(config);
+        validator.check_item_references(config);
+
+        for profile in &config.profiles {
+            // Profiles are used one at a time, so only sources of the same
+            // profile can get in each other's way
+            let mut seen_sources: HashMap<(PathBuf, PathBuf), &Source> = HashMap::new();
+            validator.check_path(
+                profile,
+                None,
+                &profile.base_path,
+                IssueSeverity::Warning,
+                "Base path",
+            );
+
+            for source in &profile.sources {
+                validator.check_source(profile, source);
+
+                let key = (
+                    PathExpander::normalize(&source.get_source_path(&profile.variables)),
+                    PathExpander::normalize(
+                        &source.get_target_path(&profile.base_path, &profile.variables),
+                    ),
+                );
+                if let Some(existing) = seen_sources.get(&key) {
+                    validator.push(
+                        IssueSeverity::Warning,
+                        IssueKind::DuplicateSource,
+                        format!(
+                            "Sources \"{}\" and \"{}\" have the same source and target paths",
+                            existing.name, source.name
+                        ),
+                        profile,
+                        Some(source),
+                    );
+                } else {
+                    seen_sources.insert(key, source);
+                }
+            }
+        }
+
+        validator.issues
+    }
+
+    fn check_ids(&mut self, config: &AppConfig) {
+        let mut profile_ids = HashSet::new();
+        let mut source_ids = HashSet::new();
+
+        for profile in &config.profiles {
+            if !profile_ids.insert(profile.id.as_str()) {
+                self.push(
+                    IssueSeverity::Error,
+                    IssueKind::DuplicateProfileId,
+                    format!(
+                        "Profile \"{}\" has the same ID as another profile",
+                        profile.name
+                    ),
+                    profile,
+                    None,
+                );
+            }
+
+            for source in &profile.sources {
+                if !source_ids.insert(source.id.as_str()) {
+                    self.push(
+                        IssueSeverity::Error,
+                        IssueKind::DuplicateSourceId,
+                        format!(
+                            "Source \"{}\" has the same ID as another source",
+                            source.name
+                        ),
+                        profile,
+                        Some(source),
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_active_profile(&mut self, config: &AppConfig) {
+        if let Some(active_id) = &config.active_profile_id {
+            if !config.profiles.iter().any(|p| &p.id == active_id) {
+                self.issues.push(ConfigIssue {
+                    severity: IssueSeverity::Warning,
+                    kind: IssueKind::MissingActiveProfile,
+                    message: format!("Active profile \"{}\" does not exist", active_id),
+                    profile_id: None,
+                    source_id: None,
+                });
+            }
+        }
+    }
+
+    fn check_includes(&mut self, config: &AppConfig) {
+        for profile in config.profiles.iter().filter(|p| !p.includes.is_empty()) {
+            if let Err(error) = config.effective_profile(&profile.id) {
+                self.push(
+                    IssueSeverity::Error,
+                    IssueKind::InvalidInclude,
+                    format!("Profile \"{}\" can't be composed: {}", profile.name, error),
+                    profile,
+                    None,
+                );
+            }
+        }
+    }
+
+    /// Check that requirements and incompatibilities refer to sources of
+    /// the profile, which may come from an included profile.
+    fn check_item_references(&mut self, config: &AppConfig) {
+        for profile in &config.profiles {
+            let Ok(effective) = config.effective_profile(&profile.id) else {
+                continue;
+            };
+            for source in &profile.sources {
+                for item in &source.items {
+                    let missing = item
+                        .requires
+                        .iter()
+                        .chain(&item.conflicts)
+                        .filter_map(|reference| reference.source_id.as_ref())
+                        .find(|id| !effective.sources.iter().any(|s| s.id == **id));
+                    if missing.is_some() {
+                        self.push(
+                            IssueSeverity::Warning,
+                            IssueKind::InvalidItemReference,
+                            format!(
+                                "Item \"{}\" in source \"{}\" refers to a source that isn't part of the profile",
+                                item.name, source.name
+                            ),
+                            profile,
+                            Some(source),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_source(&mut self, profile: &Profile, source: &Source) {
+        self.check_path(
+            profile,
+            Some(source),
+            &source.source_path,
+            IssueSeverity::Error,
+            "Source path",
+        );
+        for target_path in source.target_path.iter().chain(&source.additional_targets) {
+            self.check_path(
+                profile,
+                Some(source),
+                target_path,
+                IssueSeverity::Warning,
+                "Target path",
+            );
+        }
+        for item in &source.items {
+            if let Some(target_path) = &item.target_path {
+                self.check_path(
+                    profile,
+                    Some(source),
+                    target_path,
+                    IssueSeverity::Warning,
+                    &format!("Target path of item \"{}\"", item.name),
+                );
+            }
+        }
+
+        let source_dir = PathExpander::normalize(&source.get_source_path(&profile.variables));
+        let item_targets = source
+            .items
+            .iter()
+            .filter_map(|item| item.target_path.as_deref())
+            .map(|path| PathExpander::expand(path, &profile.variables));
+        let links_into_source = source
+            .get_target_paths(&profile.base_path, &profile.variables)
+            .into_iter()
+            .chain(item_targets)
+            .any(|target_path| PathExpander::normalize(&target_path).starts_with(&source_dir));
+        if links_into_source {
+            self.push(
+                IssueSeverity::Error,
+                IssueKind::TargetInsideSource,
+                format!(
+                    "Source \"{}\" links into its own source folder, which would create link loops",
+                    source.name
+                ),
+                profile,
+                Some(source),
+            );
+        }
+
+        let conditions = source
+            .condition
+            .iter()
+            .map(|condition| (None, condition))
+            .chain(
+                source
+                    .item_conditions
+                    .iter()
+                    .map(|(item_name, condition)| (Some(item_name), condition)),
+            );
+        for (item_name, condition) in conditions {
+            self.check_condition(profile, source, item_name, condition);
+        }
+
+        for rule in &source.auto_enable {
+            if let AutoEnableRule::Matching { pattern } = rule {
+                if let Err(e) = Pattern::new(pattern) {
+                    self.push(
+                        IssueSeverity::Warning,
+                        IssueKind::InvalidAutoEnableRule,
+                        format!(
+                            "Auto-enable pattern \"{}\" of source \"{}\" is invalid: {}",
+                            pattern, source.name, e.msg
+                        ),
+                        profile,
+                        Some(source),
+                    );
+                }
+            }
+        }
+
+        let mut seen_items = HashSet::new();
+        for item_name in source.items.iter().map(|item| &item.name) {
+            if !seen_items.insert(item_name) {
+                self.push(
+                    IssueSeverity::Warning,
+                    IssueKind::DuplicateEnabledItem,
+                    format!(
+                        "Item \"{}\" is listed more than once in source \"{}\"",
+                        item_name, source.name
+                    ),
+                    profile,
+                    Some(source),
+                );
+            }
+        }
+    }
+
+    fn check_condition(
+        &mut self,
+        profile: &Profile,
+        source: &Source,
+        item_name: Option<&String>,
+        condition: &Condition,
+    ) {
+        let label = match item_name {
+            Some(item_name) => format!("Item \"{}\" of source \"{}\"", item_name, source.name),
+            None => format!("Source \"{}\"", source.name),
+        };
+
+        if let Some(pattern) = &condition.hostname {
+            if Pattern::new(pattern).is_err() {
+                self.push(
+                    IssueSeverity::Error,
+                    IssueKind::InvalidCondition,
+                    format!(
+                        "{} has an invalid hostname pattern and never applies: {}",
+                        label, pattern
+                    ),
+                    profile,
+                    Some(source),
+                );
+            }
+        }
+
+        for os in &condition.os {
+            if !KNOWN_OS_NAMES.contains(&os.to_lowercase().as_str()) {
+                self.push(
+                    IssueSeverity::Warning,
+                    IssueKind::InvalidCondition,
+                    format!("{} refers to an unknown operating system: {}", label, os),
+                    profile,
+                    Some(source),
+                );
+            }
+        }
+    }
+
+    fn check_path(
+        &mut self,
+        profile: &Profile,
+        source: Option<&Source>,
+        path: &str,
+        severity: IssueSeverity,
+        label: &str,
+    ) {
+        let undefined = PathExpander::undefined_variables(path, &profile.variables);
+        if !undefined.is_empty() {
+            self.push(
+                IssueSeverity::Error,
+                IssueKind::UndefinedVariable,
+                format!(
+                    "{} uses undefined variables ({}): {}",
+                    label,
+                    undefined.join(", "),
+                    path
+                ),
+                profile,
+                source,
+            );
+            return;
+        }
+
+        if !PathExpander::expand(path, &profile.variables).exists() {
+            self.push(
+                severity,
+                IssueKind::MissingPath,
+                format!("{} does not exist: {}", label, path),
+                profile,
+                source,
+            );
+        }
+    }
+
+    fn push(
+        &mut self,
+        severity: IssueSeverity,
+        kind: IssueKind,
+        message: String,
+        profile: &Profile,
+        source: Option<&Source>,
+    ) {
+        self.issues.push(ConfigIssue {
+            severity,
+            kind,
+            message,
+            profile_id: Some(profile.id.clone()),
+            source_id: source.map(|s| s.id.clone()),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config(value: serde_json::Value) -> AppConfig {
+        serde_json::from_value(value).unwrap()
+    }
+
+    /// List the issues as their kind and the profile and source they point
+    /// at, leaving out paths missing on this machine.
+    fn issues(config: &AppConfig) -> Vec<(IssueKind, Option<String>, Option<String>)> {
+        ConfigValidator::validate(config)
+            .into_iter()
+            .filter(|issue| issue.kind != IssueKind::MissingPath)
+            .map(|issue| (issue.kind, issue.profile_id, issue.source_id))
+            .collect()
+    }
+
+    fn source(id: &str, source_path: &str) -> serde_json::Value {
+        json!({ "id": id, "name": id, "sourcePath": source_path })
+    }
+
+    fn issue(
+        kind: IssueKind,
+        profile_id: &str,
+        source_id: Option<&str>,
+    ) -> (IssueKind, Option<String>, Option<String>) {
+        (
+            kind,
+            Some(profile_id.to_string()),
+            source_id.map(String::from),
+        )
+    }
+
+    #[test]
+    fn accepts_a_valid_config() {
+        let config = config(json!({
+            "version": 1,
+            "activeProfileId": "home",
+            "profiles": [{
+                "id": "home",
+                "name": "Home",
+                "basePath": "/home/alice",
+                "sources": [source("dotfiles", "/opt/dotfiles")]
+            }]
+        }));
+
+        assert!(issues(&config).is_empty());
+    }
+
+    #[test]
+    fn reports_duplicate_sources_within_a_profile_only() {
+        let config = config(json!({
+            "version": 1,
+            "profiles": [
+                {
+                    "id": "home",
+                    "name": "Home",
+                    "basePath": "/home/alice",
+                    "sources": [source("a", "/opt/dotfiles"), source("b", "/opt/dotfiles/")]
+                },
+                {
+                    "id": "work",
+                    "name": "Work",
+                    "basePath": "/home/alice",
+                    "sources": [source("c", "/opt/dotfiles")]
+                }
+            ]
+        }));
+
+        assert_eq!(
+            issues(&config),
+            vec![issue(IssueKind::DuplicateSource, "home", Some("b"))]
+        );
+    }
+
+    #[test]
+    fn reports_duplicate_ids_and_a_missing_active_profile() {
+        let config = config(json!({
+            "version": 1,
+            "activeProfileId": "gone",
+            "profiles": [
+                {
+                    "id": "home",
+                    "name": "Home",
+                    "basePath": "/home/alice",
+                    "sources": [source("a", "/opt/one")]
+                },
+                {
+                    "id": "home",
+                    "name": "Work",
+                    "basePath": "/home/alice",
+                    "sources": [source("a", "/opt/two")]
+                }
+            ]
+        }));
+
+        assert_eq!(
+            issues(&config),
+            vec![
+                issue(IssueKind::DuplicateProfileId, "home", None),
+                issue(IssueKind::DuplicateSourceId, "home", Some("a")),
+                (IssueKind::MissingActiveProfile, None, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_include_cycles_instead_of_item_references() {
+        let config = config(json!({
+            "version": 1,
+            "profiles": [
+                {
+                    "id": "home",
+                    "name": "Home",
+                    "basePath": "/home/alice",
+                    "includes": ["work"],
+                    "sources": [source("a", "/opt/home")]
+                },
+                {
+                    "id": "work",
+                    "name": "Work",
+                    "basePath": "/home/alice",
+                    "includes": ["home"],
+                    "sources": [{
+                        "id": "b",
+                        "name": "b",
+                        "sourcePath": "/opt/work",
+                        "items": [{ "name": "vimrc", "requires": [{ "sourceId": "c", "name": "x" }] }]
+                    }]
+                }
+            ]
+        }));
+
+        assert_eq!(
+            issues(&config),
+            vec![
+                issue(IssueKind::InvalidInclude, "home", None),
+                issue(IssueKind::InvalidInclude, "work", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_problems_of_a_source() {
+        let config = config(json!({
+            "version": 1,
+            "profiles": [{
+                "id": "home",
+                "name": "Home",
+                "basePath": "/home/alice",
+                "sources": [{
+                    "id": "a",
+                    "name": "a",
+                    "sourcePath": "/opt/dotfiles",
+                    "targetPath": "/opt/dotfiles/linked",
+                    "condition": { "os": ["linux", "beos"], "hostname": "[work" },
+                    "autoEnable": [{ "kind": "matching", "pattern": "[*.esp" }],
+                    "items": [
+                        { "name": "vimrc", "enabled": true },
+                        { "name": "vimrc" },
+                        { "name": "zshrc", "targetPath": "$CONFIG/zsh" },
+                        { "name": "gitconfig", "requires": [{ "sourceId": "gone", "name": "x" }] }
+                    ]
+                }]
+            }]
+        }));
+
+        let kinds = issues(&config)
+            .into_iter()
+            .map(|(kind, _, _)| kind)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                IssueKind::InvalidItemReference,
+                IssueKind::UndefinedVariable,
+                IssueKind::TargetInsideSource,
+                IssueKind::InvalidCondition,
+                IssueKind::InvalidCondition,
+                IssueKind::InvalidAutoEnableRule,
+                IssueKind::DuplicateEnabledItem,
+            ]
+        );
+    }
+}
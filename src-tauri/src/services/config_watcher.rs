@@ -0,0 +1,58 @@
+use crate::models::ConfigReloadFailed;
+use crate::services::{AppEvent, OperationQueue};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use tauri::AppHandle;
+
+/// Watches the config file and reloads it through the operation queue when
+/// it is edited outside the app.
+pub struct ConfigWatcher;
+
+impl ConfigWatcher {
+    pub fn spawn(app: AppHandle, config_path: PathBuf, queue: OperationQueue) {
+        thread::Builder::new()
+            .name("config-watcher".to_string())
+            .spawn(move || {
+                if let Err(e) = Self::watch(&config_path, &queue) {
+                    AppEvent::ConfigReloadFailed(ConfigReloadFailed {
+                        error: format!("Edits to the config file are no longer picked up: {}", e),
+                    })
+                    .emit(&app);
+                }
+            })
+            .expect("Failed to spawn config watcher");
+    }
+
+    fn watch(config_path: &Path, queue: &OperationQueue) -> Result<(), String> {
+        // Watch the directory rather than the file, since editors often save
+        // by replacing the file
+        let directory = config_path
+            .parent()
+            .ok_or("Config file has no parent directory")?;
+
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender).map_err(|e| e.to_string())?;
+        watcher
+            .watch(directory, RecursiveMode::NonRecursive)
+            .map_err(|e| e.to_string())?;
+
+        for event in receiver {
+            let Ok(event): Result<Event, _> = event else {
+                continue;
+            };
+            let touches_config = event
+                .paths
+                .iter()
+                .any(|p| p.file_name() == config_path.file_name());
+
+            if touches_config && matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                // Failures are reported to the UI through events
+                let _ = queue.reload_from_disk();
+            }
+        }
+
+        Ok(())
+    }
+}
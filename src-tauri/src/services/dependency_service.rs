@@ -0,0 +1,304 @@
+use crate::models::{ItemConfig, ItemRef, Profile, RelatedItem, ToggleCheck};
+use std::collections::HashSet;
+
+/// An item, identified by its source's ID and its name.
+type ItemKey = (String, String);
+
+/// Works out the requirements and incompatibilities declared in the item
+/// settings of a profile. References may cross sources, including those of
+/// included profiles.
+pub struct DependencyService;
+
+impl DependencyService {
+    /// Find what enabling or disabling an item would affect.
+    pub fn check(
+        profile: &Profile,
+        source_id: &str,
+        item_name: &str,
+        enabled: bool,
+    ) -> ToggleCheck {
+        if enabled {
+            ToggleCheck {
+                missing_requirements: Self::missing_requirements(profile, source_id, item_name),
+                conflicts: Self::conflicts(profile, source_id, item_name),
+                dependents: Vec::new(),
+            }
+        } else {
+            ToggleCheck {
+                dependents: Self::dependents(profile, source_id, item_name),
+                ..ToggleCheck::default()
+            }
+        }
+    }
+
+    /// Find the items an item needs, directly or through other items, that
+    /// aren't enabled.
+    pub fn missing_requirements(
+        profile: &Profile,
+        source_id: &str,
+        item_name: &str,
+    ) -> Vec<RelatedItem> {
+        let start = (source_id.to_string(), item_name.to_string());
+        let mut seen = HashSet::from([start.clone()]);
+        let mut pending = vec![start];
+        let mut missing = Vec::new();
+
+        while let Some((source_id, item_name)) = pending.pop() {
+            for requirement in requirements(profile, &source_id, &item_name) {
+                if !seen.insert(requirement.clone()) {
+                    continue;
+                }
+                if !is_enabled(profile, &requirement) {
+                    missing.push(related(profile, &requirement));
+                }
+                pending.push(requirement);
+            }
+        }
+
+        missing
+    }
+
+    /// Find the enabled items an item is incompatible with. Declaring it on
+    /// either of the two items is enough.
+    pub fn conflicts(profile: &Profile, source_id: &str, item_name: &str) -> Vec<RelatedItem> {
+        let item = (source_id.to_string(), item_name.to_string());
+        let declared = references(profile, source_id, item_name, |config| &config.conflicts);
+
+        enabled_items(profile)
+            .filter(|other| *other != item)
+            .filter(|other| {
+                declared.contains(other)
+                    || references(profile, &other.0, &other.1, |config| &config.conflicts)
+                        .contains(&item)
+            })
+            .map(|other| related(profile, &other))
+            .collect()
+    }
+
+    /// Describe why an item is refused for the incompatible items found by
+    /// `conflicts`, if there are any.
+    pub fn conflict_error(conflicts: &[RelatedItem]) -> Option<String> {
+        if conflicts.is_empty() {
+            return None;
+        }
+        let names = conflicts
+            .iter()
+            .map(|item| format!("\"{}\" in {}", item.item_name, item.source_name))
+            .collect::<Vec<_>>();
+        Some(format!("Incompatible with {}", names.join(", ")))
+    }
+
+    /// Find the enabled items that need an item, directly or through other
+    /// items.
+    pub fn dependents(profile: &Profile, source_id: &str, item_name: &str) -> Vec<RelatedItem> {
+        let mut broken = HashSet::from([(source_id.to_string(), item_name.to_string())]);
+        let mut dependents = Vec::new();
+
+        // Repeat until no more items depend on the broken ones
+        loop {
+            let newly_broken = enabled_items(profile)
+                .filter(|item| !broken.contains(item))
+                .filter(|item| {
+                    requirements(profile, &item.0, &item.1)
+                        .iter()
+                        .any(|requirement| broken.contains(requirement))
+                })
+                .collect::<Vec<_>>();
+            if newly_broken.is_empty() {
+                break;
+            }
+            for item in newly_broken {
+                dependents.push(related(profile, &item));
+                broken.insert(item);
+            }
+        }
+
+        dependents
+    }
+}
+
+fn requirements(profile: &Profile, source_id: &str, item_name: &str) -> Vec<ItemKey> {
+    references(profile, source_id, item_name, |config| &config.requires)
+}
+
+/// Resolve the references in an item's settings, which default to the
+/// item's own source.
+fn references(
+    profile: &Profile,
+    source_id: &str,
+    item_name: &str,
+    list: impl Fn(&ItemConfig) -> &Vec<ItemRef>,
+) -> Vec<ItemKey> {
+    profile
+        .sources
+        .iter()
+        .find(|source| source.id == source_id)
+        .and_then(|source| source.item(item_name))
+        .map(|config| {
+            list(config)
+                .iter()
+                .map(|reference| {
+                    let source_id = reference.source_id.as_deref().unwrap_or(source_id);
+                    (source_id.to_string(), reference.name.clone())
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn enabled_items(profile: &Profile) -> impl Iterator<Item = ItemKey> + '_ {
+    profile.sources.iter().flat_map(|source| {
+        source
+            .enabled_items()
+            .into_iter()
+            .map(|item_name| (source.id.clone(), item_name))
+    })
+}
+
+fn is_enabled(profile: &Profile, (source_id, item_name): &ItemKey) -> bool {
+    profile
+        .sources
+        .iter()
+        .any(|source| source.id == *source_id && source.is_enabled(item_name))
+}
+
+fn related(profile: &Profile, (source_id, item_name): &ItemKey) -> RelatedItem {
+    let source_name = profile
+        .sources
+        .iter()
+        .find(|source| source.id == *source_id)
+        .map(|source| source.name.clone())
+        .unwrap_or_else(|| "a missing source".to_string());
+
+    RelatedItem {
+        source_id: source_id.clone(),
+        source_name,
+        item_name: item_name.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Source;
+
+    /// Set up an item of a source with the items it requires, by
+    /// `source_id/name` for items of other sources.
+    fn set_item(source: &mut Source, name: &str, enabled: bool, requires: &[&str]) {
+        let item = source.item_mut(name);
+        item.enabled = enabled;
+        item.requires = requires
+            .iter()
+            .map(|reference| match reference.split_once('/') {
+                Some((source_id, name)) => ItemRef {
+                    source_id: Some(source_id.to_string()),
+                    name: name.to_string(),
+                },
+                None => ItemRef {
+                    source_id: None,
+                    name: reference.to_string(),
+                },
+            })
+            .collect();
+    }
+
+    fn profile(sources: Vec<Source>) -> Profile {
+        let mut profile = Profile::new("Test".to_string(), "/home/me".to_string());
+        profile.sources = sources;
+        profile
+    }
+
+    fn source(id: &str) -> Source {
+        let mut source = Source::new(id.to_string(), format!("/dotfiles/{}", id), None);
+        source.id = id.to_string();
+        source
+    }
+
+    fn names(items: &[RelatedItem]) -> Vec<&str> {
+        items.iter().map(|item| item.item_name.as_str()).collect()
+    }
+
+    #[test]
+    fn missing_requirements_follow_chains() {
+        let mut shell = source("shell");
+        set_item(&mut shell, "zshrc", false, &["aliases"]);
+        set_item(&mut shell, "aliases", false, &["env"]);
+        set_item(&mut shell, "env", true, &[]);
+
+        let profile = profile(vec![shell]);
+
+        assert_eq!(
+            names(&DependencyService::missing_requirements(
+                &profile, "shell", "zshrc"
+            )),
+            vec!["aliases"]
+        );
+    }
+
+    #[test]
+    fn missing_requirements_stop_at_cycles() {
+        let mut shell = source("shell");
+        set_item(&mut shell, "a", false, &["b"]);
+        set_item(&mut shell, "b", false, &["c"]);
+        set_item(&mut shell, "c", false, &["a"]);
+
+        let profile = profile(vec![shell]);
+
+        assert_eq!(
+            names(&DependencyService::missing_requirements(
+                &profile, "shell", "a"
+            )),
+            vec!["b", "c"]
+        );
+    }
+
+    #[test]
+    fn missing_requirements_cross_sources() {
+        let mut shell = source("shell");
+        set_item(&mut shell, "zshrc", false, &["fonts/nerd"]);
+        let fonts = source("fonts");
+
+        let profile = profile(vec![shell, fonts]);
+        let missing = DependencyService::missing_requirements(&profile, "shell", "zshrc");
+
+        assert_eq!(
+            missing,
+            vec![RelatedItem {
+                source_id: "fonts".to_string(),
+                source_name: "fonts".to_string(),
+                item_name: "nerd".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn dependents_follow_chains() {
+        let mut shell = source("shell");
+        set_item(&mut shell, "env", true, &[]);
+        set_item(&mut shell, "aliases", true, &["env"]);
+        set_item(&mut shell, "zshrc", true, &["aliases"]);
+        set_item(&mut shell, "bashrc", false, &["env"]);
+
+        let profile = profile(vec![shell]);
+
+        assert_eq!(
+            names(&DependencyService::dependents(&profile, "shell", "env")),
+            vec!["aliases", "zshrc"]
+        );
+    }
+
+    #[test]
+    fn dependents_stop_at_cycles() {
+        let mut shell = source("shell");
+        set_item(&mut shell, "a", true, &["b"]);
+        set_item(&mut shell, "b", true, &["c"]);
+        set_item(&mut shell, "c", true, &["a"]);
+
+        let profile = profile(vec![shell]);
+
+        assert_eq!(
+            names(&DependencyService::dependents(&profile, "shell", "c")),
+            vec!["b", "a"]
+        );
+    }
+}
@@ -0,0 +1,39 @@
+use crate::models::{
+    ConfigChanged, ConfigConflict, ConfigReloadFailed, ConfigSaveFailed, ItemStatusChanged,
+    ProfileApplied, SourceWatchFailed,
+};
+use tauri::{AppHandle, Emitter};
+
+pub const CONFIG_CHANGED_EVENT: &str = "config-changed";
+pub const PROFILE_APPLIED_EVENT: &str = "profile-applied";
+pub const ITEM_STATUS_CHANGED_EVENT: &str = "item-status-changed";
+pub const CONFIG_CONFLICT_EVENT: &str = "config-conflict";
+pub const CONFIG_RELOAD_FAILED_EVENT: &str = "config-reload-failed";
+pub const CONFIG_SAVE_FAILED_EVENT: &str = "config-save-failed";
+pub const SOURCE_WATCH_FAILED_EVENT: &str = "source-watch-failed";
+
+/// Events raised by operations, emitted once their changes are committed.
+#[derive(Debug, Clone)]
+pub enum AppEvent {
+    ConfigChanged(ConfigChanged),
+    ProfileApplied(ProfileApplied),
+    ItemStatusChanged(ItemStatusChanged),
+    ConfigConflict(ConfigConflict),
+    ConfigReloadFailed(ConfigReloadFailed),
+    ConfigSaveFailed(ConfigSaveFailed),
+    SourceWatchFailed(SourceWatchFailed),
+}
+
+impl AppEvent {
+    pub fn emit(&self, app: &AppHandle) {
+        let _ = match self {
+            AppEvent::ConfigChanged(payload) => app.emit(CONFIG_CHANGED_EVENT, payload),
+            AppEvent::ProfileApplied(payload) => app.emit(PROFILE_APPLIED_EVENT, payload),
+            AppEvent::ItemStatusChanged(payload) => app.emit(ITEM_STATUS_CHANGED_EVENT, payload),
+            AppEvent::ConfigConflict(payload) => app.emit(CONFIG_CONFLICT_EVENT, payload),
+            AppEvent::ConfigReloadFailed(payload) => app.emit(CONFIG_RELOAD_FAILED_EVENT, payload),
+            AppEvent::ConfigSaveFailed(payload) => app.emit(CONFIG_SAVE_FAILED_EVENT, payload),
+            AppEvent::SourceWatchFailed(payload) => app.emit(SOURCE_WATCH_FAILED_EVENT, payload),
+        };
+    }
+}
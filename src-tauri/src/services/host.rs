@@ -0,0 +1,158 @@
+use crate::models::{Condition, Source};
+use glob::{MatchOptions, Pattern};
+
+/// Operating system names and families a condition can refer to.
+pub const KNOWN_OS_NAMES: &[&str] = &[
+    "linux", "macos", "windows", "freebsd", "openbsd", "netbsd", "unix",
+];
+
+/// The machine the app is running on, as seen by conditions.
+pub struct Host {
+    pub os: &'static str,
+    pub family: &'static str,
+    pub arch: &'static str,
+    pub hostname: String,
+    pub user: String,
+}
+
+impl Host {
+    pub fn current() -> Self {
+        Self {
+            os: std::env::consts::OS,
+            family: std::env::consts::FAMILY,
+            arch: std::env::consts::ARCH,
+            hostname: tauri_plugin_os::hostname(),
+            user: std::env::var("USER")
+                .or_else(|_| std::env::var("USERNAME"))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Check whether an item of a source applies to this machine.
+    pub fn applies(&self, source: &Source, item_name: &str) -> bool {
+        source
+            .conditions(item_name)
+            .all(|condition| self.matches(condition))
+    }
+
+    /// Check whether this machine runs the given operating system or family.
+    pub fn is_os(&self, name: &str) -> bool {
+        name.eq_ignore_ascii_case(self.os)
+            || name.eq_ignore_ascii_case(self.family)
+            || (self.os == "macos" && name.eq_ignore_ascii_case("darwin"))
+    }
+
+    /// Check whether this machine meets a condition.
+    pub fn matches(&self, condition: &Condition) -> bool {
+        let os_matches = condition.os.is_empty() || condition.os.iter().any(|os| self.is_os(os));
+
+        let hostname_matches = match &condition.hostname {
+            Some(pattern) => {
+                let options = MatchOptions {
+                    case_sensitive: false,
+                    ..MatchOptions::new()
+                };
+                Pattern::new(pattern)
+                    .map(|p| p.matches_with(&self.hostname, options))
+                    .unwrap_or(false)
+            }
+            None => true,
+        };
+
+        let env_matches = condition
+            .env
+            .iter()
+            .all(|name| std::env::var_os(name).is_some_and(|value| !value.is_empty()));
+
+        os_matches && hostname_matches && env_matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host() -> Host {
+        Host {
+            os: "macos",
+            family: "unix",
+            arch: "aarch64",
+            hostname: "Work-Laptop.local".to_string(),
+            user: "alice".to_string(),
+        }
+    }
+
+    fn os(names: &[&str]) -> Condition {
+        Condition {
+            os: names.iter().map(|name| name.to_string()).collect(),
+            ..Condition::default()
+        }
+    }
+
+    fn hostname(pattern: &str) -> Condition {
+        Condition {
+            hostname: Some(pattern.to_string()),
+            ..Condition::default()
+        }
+    }
+
+    #[test]
+    fn matches_operating_systems_and_families() {
+        let host = host();
+
+        assert!(host.matches(&Condition::default()));
+        assert!(host.matches(&os(&["linux", "macos"])));
+        assert!(host.matches(&os(&["Unix"])));
+        assert!(host.matches(&os(&["darwin"])));
+        assert!(!host.matches(&os(&["linux", "windows"])));
+    }
+
+    #[test]
+    fn matches_hostname_patterns_ignoring_case() {
+        let host = host();
+
+        assert!(host.matches(&hostname("work-laptop.local")));
+        assert!(host.matches(&hostname("work-*")));
+        assert!(!host.matches(&hostname("work-laptop")));
+        assert!(!host.matches(&hostname("home-*")));
+        // An invalid pattern never matches
+        assert!(!host.matches(&hostname("[work")));
+    }
+
+    #[test]
+    fn needs_every_environment_variable_and_every_part() {
+        let host = host();
+        let env = |names: &[&str]| Condition {
+            env: names.iter().map(|name| name.to_string()).collect(),
+            ..Condition::default()
+        };
+
+        // Cargo sets this while running tests
+        assert!(host.matches(&env(&["CARGO_MANIFEST_DIR"])));
+        assert!(!host.matches(&env(&["CARGO_MANIFEST_DIR", "SYMLINK_MANAGER_UNSET"])));
+        assert!(!host.matches(&Condition {
+            hostname: Some("home-*".to_string()),
+            ..os(&["macos"])
+        }));
+    }
+
+    #[test]
+    fn items_need_their_own_and_their_source_condition() {
+        let host = host();
+        let mut source = Source::new("Dotfiles".to_string(), "/dotfiles".to_string(), None);
+        source.condition = Some(os(&["unix"]));
+        source
+            .item_conditions
+            .insert("karabiner".to_string(), os(&["macos"]));
+        source
+            .item_conditions
+            .insert("i3".to_string(), os(&["linux"]));
+
+        assert!(host.applies(&source, "karabiner"));
+        assert!(host.applies(&source, "zshrc"));
+        assert!(!host.applies(&source, "i3"));
+
+        source.condition = Some(os(&["windows"]));
+        assert!(!host.applies(&source, "karabiner"));
+    }
+}
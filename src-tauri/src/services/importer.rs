@@ -0,0 +1,545 @@
+use crate::models::{Profile, Source};
+use crate::services::LinkService;
+use crate::utils::{PathExpander, Yaml};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A link described by an existing layout.
+struct LinkSpec {
+    source: PathBuf,
+    link: PathBuf,
+}
+
+/// Converts layouts managed by other tools into profiles.
+pub struct Importer;
+
+impl Importer {
+    /// Import a GNU stow directory. Every package becomes a source linking
+    /// into the stow target, which defaults to the parent of the stow
+    /// directory. Items that are currently stowed are enabled, and the
+    /// `dot-` prefix of `stow --dotfiles` is mapped to a leading dot.
+    ///
+    /// Like stow, a folder of a package is only linked as a whole if the
+    /// target has nothing of that name yet, or already links to the folder.
+    /// For a folder the target already has, the contents are linked one by
+    /// one instead, by a source of their own.
+    pub fn from_stow(
+        stow_dir: &Path,
+        target_dir: Option<&Path>,
+        name: String,
+    ) -> Result<Profile, String> {
+        let target_dir = match target_dir {
+            Some(target_dir) => target_dir.to_path_buf(),
+            None => stow_dir
+                .parent()
+                .ok_or("Stow directory has no parent directory")?
+                .to_path_buf(),
+        };
+
+        let mut profile = Profile::new(name, PathExpander::contract_home(&target_dir));
+
+        for package in sorted_entries(stow_dir)? {
+            let package_name = file_name(&package);
+            if !package.is_dir() || package_name.starts_with('.') {
+                continue;
+            }
+
+            stow_sources(
+                &package,
+                package_name,
+                &target_dir,
+                None,
+                &mut profile.sources,
+            )?;
+        }
+
+        if profile.sources.is_empty() {
+            return Err(format!("No stow packages found in {}", stow_dir.display()));
+        }
+
+        Ok(profile)
+    }
+
+    /// Import the `link:` directives of a dotbot `install.conf.yaml`.
+    pub fn from_dotbot(config_file: &Path, name: String) -> Result<Profile, String> {
+        let content = fs::read_to_string(config_file)
+            .map_err(|e| format!("Failed to read dotbot config: {}", e))?;
+        // dotbot reads JSON as well, which is YAML but may span lines in ways
+        // the YAML reader doesn't follow
+        let directives = if config_file.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&content)
+                .map(json_to_yaml)
+                .map_err(|e| e.to_string())
+        } else {
+            Yaml::parse(&content)
+        }
+        .map_err(|e| format!("Failed to parse dotbot config: {}", e))?;
+
+        let base_dir = config_file.parent().unwrap_or(Path::new(""));
+        let mut specs = Vec::new();
+
+        for directive in directives.as_sequence().into_iter().flatten() {
+            let Some(links) = directive.get("link").and_then(Yaml::as_mapping) else {
+                continue;
+            };
+
+            for (target, options) in links {
+                let link = expand_home(target);
+
+                // The source is either given directly, under `path`, or
+                // derived from the target name without its leading dot
+                let source = match options {
+                    Yaml::Scalar(path) => path.clone(),
+                    Yaml::Mapping(_) => match options.get("path").and_then(Yaml::as_str) {
+                        Some(path) => path.to_string(),
+                        None => default_dotbot_source(&link),
+                    },
+                    _ => default_dotbot_source(&link),
+                };
+
+                specs.push(LinkSpec {
+                    source: base_dir.join(expand_home(&source)),
+                    link,
+                });
+            }
+        }
+
+        build_profile(name, specs)
+            .ok_or_else(|| format!("No links found in {}", config_file.display()))
+    }
+
+    /// Import the `ln -s` commands of a shell script.
+    pub fn from_ln_script(script: &Path, name: String) -> Result<Profile, String> {
+        let content =
+            fs::read_to_string(script).map_err(|e| format!("Failed to read script: {}", e))?;
+
+        let mut specs = Vec::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            for command in line.split("&&").flat_map(|part| part.split(';')) {
+                if let Some(spec) = parse_ln_command(command) {
+                    specs.push(spec);
+                }
+            }
+        }
+
+        build_profile(name, specs)
+            .ok_or_else(|| format!("No ln -s commands found in {}", script.display()))
+    }
+}
+
+/// Parse a single `ln -s SOURCE DEST` command.
+fn parse_ln_command(command: &str) -> Option<LinkSpec> {
+    let words = shlex::split(command.trim())?;
+    let (program, args) = words.split_first()?;
+    if program != "ln" && !program.ends_with("/ln") {
+        return None;
+    }
+
+    let mut symbolic = false;
+    let mut paths = Vec::new();
+    for arg in args {
+        if arg == "--symbolic" {
+            symbolic = true;
+        } else if let Some(flags) = arg.strip_prefix('-').filter(|f| !f.starts_with('-')) {
+            symbolic |= flags.contains('s');
+        } else if !arg.starts_with("--") {
+            paths.push(expand_home(arg));
+        }
+    }
+
+    let [source, dest] = <[PathBuf; 2]>::try_from(paths).ok()?;
+    if !symbolic {
+        return None;
+    }
+
+    // Linking into a directory keeps the source name
+    let link = if dest.to_string_lossy().ends_with('/') || (dest.is_dir() && !dest.is_symlink()) {
+        dest.join(source.file_name()?)
+    } else {
+        dest
+    };
+
+    // Relative link targets are resolved from the directory holding the link
+    let source = match link.parent() {
+        Some(parent) if source.is_relative() => PathExpander::normalize(&parent.join(source)),
+        _ => source,
+    };
+
+    Some(LinkSpec { source, link })
+}
+
+/// Group links by source and target directory into sources. The most common
+/// target directory becomes the profile's base path.
+fn build_profile(name: String, specs: Vec<LinkSpec>) -> Option<Profile> {
+    let mut groups: BTreeMap<(PathBuf, PathBuf), Vec<LinkSpec>> = BTreeMap::new();
+    for spec in specs {
+        let (Some(source_dir), Some(target_dir)) = (spec.source.parent(), spec.link.parent())
+        else {
+            continue;
+        };
+        groups
+            .entry((source_dir.to_path_buf(), target_dir.to_path_buf()))
+            .or_default()
+            .push(spec);
+    }
+
+    let mut target_counts: BTreeMap<&PathBuf, usize> = BTreeMap::new();
+    for ((_, target_dir), specs) in &groups {
+        *target_counts.entry(target_dir).or_default() += specs.len();
+    }
+    let base_path = target_counts
+        .iter()
+        .max_by_key(|(_, count)| **count)
+        .map(|(target_dir, _)| (*target_dir).clone())?;
+
+    let mut profile = Profile::new(name, PathExpander::contract_home(&base_path));
+
+    for ((source_dir, target_dir), specs) in groups {
+        let target_path =
+            (target_dir != base_path).then(|| PathExpander::contract_home(&target_dir));
+        let mut source = Source::new(
+            file_name(&source_dir),
+            PathExpander::contract_home(&source_dir),
+            target_path,
+        );
+
+        for spec in specs {
+            let item_name = file_name(&spec.source);
+            let link_name = file_name(&spec.link);
+
+            if item_name.starts_with('.') {
+                source.include_hidden = true;
+            }
+            if link_name != item_name {
+                source.link_names.insert(item_name.clone(), link_name);
+            }
+            source.set_enabled(&item_name, true);
+        }
+
+        profile.sources.push(source);
+    }
+
+    Some(profile)
+}
+
+/// Turn a folder of a stow package into a source linking into `target_dir`,
+/// skipping the folders that are linked one by one as stow does. Those
+/// become sources of their own, named after their path in the package.
+fn stow_sources(
+    dir: &Path,
+    name: String,
+    target_dir: &Path,
+    target_path: Option<String>,
+    sources: &mut Vec<Source>,
+) -> Result<(), String> {
+    let mut source = Source::new(name.clone(), PathExpander::contract_home(dir), target_path);
+    let mut has_items = false;
+    let mut unfolded = Vec::new();
+
+    for item in sorted_entries(dir)? {
+        let item_name = file_name(&item);
+        if item_name == ".git" || item_name.starts_with(".stow") {
+            continue;
+        }
+
+        let link_name = match item_name.strip_prefix("dot-") {
+            Some(rest) => format!(".{}", rest),
+            None => item_name.clone(),
+        };
+        let link = target_dir.join(&link_name);
+
+        // A folder the target has for real isn't replaced by a link
+        if item.is_dir() && link.is_dir() && !link.is_symlink() {
+            source.ignore.push(glob::Pattern::escape(&item_name));
+            unfolded.push((item, format!("{}/{}", name, link_name), link));
+            continue;
+        }
+
+        has_items = true;
+        if item_name.starts_with('.') {
+            source.include_hidden = true;
+        }
+        if link_name != item_name {
+            source.link_names.insert(item_name.clone(), link_name);
+        }
+        if LinkService::points_to(&link, &item) {
+            source.set_enabled(&item_name, true);
+        }
+    }
+
+    if has_items {
+        sources.push(source);
+    }
+    for (dir, name, link) in unfolded {
+        let target_path = Some(PathExpander::contract_home(&link));
+        stow_sources(&dir, name, &link, target_path, sources)?;
+    }
+    Ok(())
+}
+
+fn json_to_yaml(value: serde_json::Value) -> Yaml {
+    match value {
+        serde_json::Value::Null => Yaml::Null,
+        serde_json::Value::Bool(value) => Yaml::Scalar(value.to_string()),
+        serde_json::Value::Number(value) => Yaml::Scalar(value.to_string()),
+        serde_json::Value::String(value) => Yaml::Scalar(value),
+        serde_json::Value::Array(items) => {
+            Yaml::Sequence(items.into_iter().map(json_to_yaml).collect())
+        }
+        serde_json::Value::Object(entries) => Yaml::Mapping(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key, json_to_yaml(value)))
+                .collect(),
+        ),
+    }
+}
+
+fn default_dotbot_source(link: &Path) -> String {
+    let name = file_name(link);
+    name.strip_prefix('.').unwrap_or(&name).to_string()
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    PathExpander::expand(path, &BTreeMap::new())
+}
+
+fn sorted_entries(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut entries = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory: {}", e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect::<Vec<_>>();
+    entries.sort();
+    Ok(entries)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    /// A folder in the temp directory, removed again when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            let path = std::env::temp_dir().join(format!("symlink-manager-{}", Uuid::new_v4()));
+            fs::create_dir_all(&path).unwrap();
+            Self(fs::canonicalize(path).unwrap())
+        }
+
+        fn write(&self, path: &str, content: &str) -> PathBuf {
+            let path = self.0.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, content).unwrap();
+            path
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// Sum up each source as its folder, its own target folder and its
+    /// enabled items with their link names. Paths inside `root` are given
+    /// relative to it.
+    fn sources(profile: &Profile, root: &Path) -> Vec<(String, Option<String>, Vec<String>)> {
+        let relative = |path: &str| match expand_home(path).strip_prefix(root) {
+            Ok(rest) => rest.to_string_lossy().to_string(),
+            Err(_) => path.to_string(),
+        };
+
+        let mut sources = profile
+            .sources
+            .iter()
+            .map(|source| {
+                let items = source
+                    .enabled_items()
+                    .iter()
+                    .map(|item| format!("{} -> {}", item, source.link_name(item)))
+                    .collect();
+                (
+                    relative(&source.source_path),
+                    source.target_path.as_deref().map(relative),
+                    items,
+                )
+            })
+            .collect::<Vec<_>>();
+        sources.sort();
+        sources
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stow_links_folders_the_target_has_one_by_one() {
+        use std::os::unix::fs::symlink;
+
+        let dir = TempDir::new();
+        let stow = dir.0.join("dotfiles");
+        let home = dir.0.join("home");
+        dir.write("dotfiles/zsh/dot-zshrc", "");
+        dir.write("dotfiles/zsh/.stow-local-ignore", "");
+        dir.write("dotfiles/nvim/.config/nvim/init.lua", "");
+        dir.write("dotfiles/git/.config/git/config", "");
+        dir.write("dotfiles/git/.gitignore_global", "");
+        dir.write("home/.config/fish/config.fish", "");
+        symlink("../dotfiles/zsh/dot-zshrc", home.join(".zshrc")).unwrap();
+        // Folded by stow, as nothing else was in the way
+        symlink(stow.join("nvim/.config/nvim"), home.join(".config/nvim")).unwrap();
+
+        let profile = Importer::from_stow(&stow, Some(&home), "Home".to_string()).unwrap();
+
+        assert_eq!(expand_home(&profile.base_path), home);
+        assert_eq!(
+            sources(&profile, &dir.0),
+            vec![
+                ("dotfiles/git".to_string(), None, vec![]),
+                (
+                    "dotfiles/git/.config".to_string(),
+                    Some("home/.config".to_string()),
+                    vec![]
+                ),
+                (
+                    "dotfiles/nvim/.config".to_string(),
+                    Some("home/.config".to_string()),
+                    vec!["nvim -> nvim".to_string()]
+                ),
+                (
+                    "dotfiles/zsh".to_string(),
+                    None,
+                    vec!["dot-zshrc -> .zshrc".to_string()]
+                ),
+            ]
+        );
+
+        let git = &profile.sources[0];
+        assert_eq!(git.name, "git");
+        assert!(git.include_hidden);
+        assert_eq!(git.ignore, vec![".config".to_string()]);
+        assert_eq!(profile.sources[1].name, "git/.config");
+    }
+
+    #[test]
+    fn stow_needs_packages() {
+        let dir = TempDir::new();
+        dir.write("dotfiles/README", "");
+
+        assert!(Importer::from_stow(&dir.0.join("dotfiles"), None, "Home".to_string()).is_err());
+    }
+
+    #[test]
+    fn dotbot_links_become_sources_by_folder() {
+        let dir = TempDir::new();
+        let config = dir.write(
+            "dotfiles/install.conf.yaml",
+            "- defaults:\n    link:\n      relink: true\n\n\
+             - clean: ['~']\n\n\
+             - link:\n    ~/.vimrc: vim/vimrc\n    ~/.config/nvim:\n      path: nvim\n      create: true\n    ~/.zshrc:\n\n\
+             - shell:\n  - [git submodule update --init, Installing submodules]\n",
+        );
+
+        let profile = Importer::from_dotbot(&config, "Dotbot".to_string()).unwrap();
+
+        assert_eq!(profile.base_path, "~");
+        assert_eq!(
+            sources(&profile, &dir.0),
+            vec![
+                (
+                    "dotfiles".to_string(),
+                    None,
+                    vec!["zshrc -> .zshrc".to_string()]
+                ),
+                (
+                    "dotfiles".to_string(),
+                    Some("~/.config".to_string()),
+                    vec!["nvim -> nvim".to_string()]
+                ),
+                (
+                    "dotfiles/vim".to_string(),
+                    None,
+                    vec!["vimrc -> .vimrc".to_string()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn dotbot_reads_json_configs() {
+        let dir = TempDir::new();
+        let config = dir.write(
+            "dotfiles/install.conf.json",
+            r#"[{ "link": { "~/.vimrc": { "path": "vimrc" } } }]"#,
+        );
+
+        let profile = Importer::from_dotbot(&config, "Dotbot".to_string()).unwrap();
+
+        assert_eq!(
+            sources(&profile, &dir.0),
+            vec![(
+                "dotfiles".to_string(),
+                None,
+                vec!["vimrc -> .vimrc".to_string()]
+            )]
+        );
+    }
+
+    #[test]
+    fn ln_scripts_keep_symbolic_links_only() {
+        let dir = TempDir::new();
+        let script = dir.write(
+            "install.sh",
+            "#!/bin/sh\n\
+             # ln -s /opt/dotfiles/old ~/.old\n\
+             ln -s /opt/dotfiles/vimrc ~/.vimrc\n\
+             mkdir -p ~/.config && ln -sf ../dotfiles/nvim ~/.config/nvim\n\
+             ln /opt/dotfiles/hard ~/.hard\n\
+             ln --symbolic \"/opt/dot files/zshrc\" ~/.zshrc; echo done\n",
+        );
+
+        let profile = Importer::from_ln_script(&script, "Script".to_string()).unwrap();
+
+        assert_eq!(profile.base_path, "~");
+        assert_eq!(
+            sources(&profile, &dir.0),
+            vec![
+                (
+                    "/opt/dot files".to_string(),
+                    None,
+                    vec!["zshrc -> .zshrc".to_string()]
+                ),
+                (
+                    "/opt/dotfiles".to_string(),
+                    None,
+                    vec!["vimrc -> .vimrc".to_string()]
+                ),
+                (
+                    "~/dotfiles".to_string(),
+                    Some("~/.config".to_string()),
+                    vec!["nvim -> nvim".to_string()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn ln_scripts_need_links() {
+        let dir = TempDir::new();
+        let script = dir.write("install.sh", "cp vimrc ~/.vimrc\nln vimrc ~/.vimrc\n");
+
+        assert!(Importer::from_ln_script(&script, "Script".to_string()).is_err());
+    }
+}
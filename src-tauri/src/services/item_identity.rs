@@ -0,0 +1,126 @@
+use crate::models::FileIdentity;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// How much of a file is hashed to recognize it.
+const SAMPLE_SIZE: u64 = 64 * 1024;
+
+/// How many entries of a folder are hashed to recognize it.
+const MAX_ENTRIES: usize = 1000;
+
+/// Recognizes items after they are renamed in a source folder, by their
+/// inode where the file system has one, or else by their content.
+pub struct ItemIdentity;
+
+impl ItemIdentity {
+    /// Describe the file or folder at `path`. Returns `None` if it doesn't
+    /// exist or has nothing to recognize it by.
+    pub fn of(path: &Path) -> Option<FileIdentity> {
+        let (directory, size) = Self::shape(path)?;
+        let identity = FileIdentity {
+            inode: Self::inode(path),
+            content: Self::content(path),
+            directory,
+            size,
+        };
+        (identity.inode.is_some() || identity.content.is_some()).then_some(identity)
+    }
+
+    /// Tell whether a path is a folder, and its size if it is a file.
+    pub fn shape(path: &Path) -> Option<(bool, Option<u64>)> {
+        let metadata = std::fs::metadata(path).ok()?;
+        Some(if metadata.is_dir() {
+            (true, None)
+        } else {
+            (false, Some(metadata.len()))
+        })
+    }
+
+    /// Get the device and inode number of a path, without following links.
+    #[cfg(unix)]
+    pub fn inode(path: &Path) -> Option<String> {
+        use std::os::unix::fs::MetadataExt;
+
+        let metadata = std::fs::symlink_metadata(path).ok()?;
+        Some(format!("{}:{}", metadata.dev(), metadata.ino()))
+    }
+
+    #[cfg(not(unix))]
+    pub fn inode(_path: &Path) -> Option<String> {
+        None
+    }
+
+    /// Hash the size and start of a file, or the names and sizes in a
+    /// folder. Empty folders have no content to recognize them by.
+    pub fn content(path: &Path) -> Option<String> {
+        let metadata = std::fs::metadata(path).ok()?;
+        let mut hasher = Fnv::new();
+
+        if metadata.is_dir() {
+            let mut count = 0;
+            hash_folder(path, &mut hasher, &mut count);
+            if count == 0 {
+                return None;
+            }
+        } else {
+            let mut sample = Vec::new();
+            File::open(path)
+                .ok()?
+                .take(SAMPLE_SIZE)
+                .read_to_end(&mut sample)
+                .ok()?;
+            hasher.write(&metadata.len().to_le_bytes());
+            hasher.write(&sample);
+        }
+
+        Some(format!("{:016x}", hasher.0))
+    }
+}
+
+/// Hash the entries of a folder and its subfolders in name order, up to
+/// `MAX_ENTRIES` in total.
+fn hash_folder(dir: &Path, hasher: &mut Fnv, count: &mut usize) {
+    let mut entries = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        if *count >= MAX_ENTRIES {
+            return;
+        }
+        *count += 1;
+
+        hasher.write(entry.file_name().to_string_lossy().as_bytes());
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if metadata.is_dir() {
+            hasher.write(b"/");
+            hash_folder(&entry.path(), hasher, count);
+            hasher.write(b"..");
+        } else {
+            hasher.write(&metadata.len().to_le_bytes());
+        }
+    }
+}
+
+/// FNV-1a, which gives the same hash on every platform and version, as the
+/// hashes are stored in the config.
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
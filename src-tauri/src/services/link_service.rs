@@ -0,0 +1,248 @@
+use crate::models::{LinkMode, Profile, Source};
+use crate::services::{AlternateIndex, Host};
+use crate::utils::PathExpander;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+#[cfg(unix)]
+use std::os::unix::fs::symlink;
+
+#[cfg(windows)]
+use std::os::windows::fs::{symlink_dir, symlink_file};
+
+/// A single link the config expects to exist on disk.
+#[derive(Debug, Clone)]
+pub struct LinkEntry {
+    pub source_id: String,
+    pub item_name: String,
+    pub source: PathBuf,
+    pub link: PathBuf,
+    pub mode: LinkMode,
+}
+
+impl LinkEntry {
+    /// Get the path stored in the link, which is relative to the link's
+    /// folder for relative links.
+    pub fn target(&self) -> PathBuf {
+        match (self.mode, self.link.parent()) {
+            (LinkMode::Relative, Some(link_dir)) => PathExpander::relative(&self.source, link_dir)
+                .unwrap_or_else(|| self.source.clone()),
+            _ => self.source.clone(),
+        }
+    }
+}
+
+/// Cross-platform symlink operations shared by the commands.
+pub struct LinkService;
+
+impl LinkService {
+    #[cfg(unix)]
+    pub fn create(source: &Path, target: &Path) -> std::io::Result<()> {
+        symlink(source, target)
+    }
+
+    #[cfg(windows)]
+    pub fn create(source: &Path, target: &Path) -> std::io::Result<()> {
+        // A relative source is resolved from the link's folder
+        let resolved = match target.parent() {
+            Some(link_dir) if source.is_relative() => link_dir.join(source),
+            _ => source.to_path_buf(),
+        };
+        if resolved.is_dir() {
+            symlink_dir(source, target)
+        } else {
+            symlink_file(source, target)
+        }
+    }
+
+    pub fn remove(path: &Path) -> std::io::Result<()> {
+        #[cfg(windows)]
+        {
+            // On Windows, directory symlinks must be removed with remove_dir,
+            // and file symlinks with remove_file. Using the wrong one gives
+            // "Access is denied" (error 5).
+            //
+            // Try remove_dir first (works for directory symlinks), then fall back
+            // to remove_file (for file symlinks). This is more reliable than
+            // checking metadata.is_dir() which can be inconsistent for symlinks.
+            match std::fs::remove_dir(path) {
+                Ok(()) => Ok(()),
+                Err(e) if e.raw_os_error() == Some(145) => {
+                    // ERROR_DIR_NOT_EMPTY - shouldn't happen for symlinks, but just in case
+                    Err(e)
+                }
+                Err(_) => {
+                    // Try remove_file for file symlinks
+                    std::fs::remove_file(path)
+                }
+            }
+        }
+
+        #[cfg(unix)]
+        {
+            std::fs::remove_file(path)
+        }
+    }
+
+    #[cfg(windows)]
+    pub fn format_error(e: std::io::Error) -> String {
+        match e.raw_os_error() {
+            Some(1314) => {
+                "Permission denied: Creating symbolic links requires either:\n\
+                 1. Enable Developer Mode in Windows Settings (Settings > Update & Security > For developers)\n\
+                 2. Run this application as Administrator"
+                    .to_string()
+            }
+            _ => e.to_string(),
+        }
+    }
+
+    #[cfg(unix)]
+    pub fn format_error(e: std::io::Error) -> String {
+        e.to_string()
+    }
+
+    /// Check whether `link` is a symlink resolving to `expected`.
+    pub fn points_to(link: &Path, expected: &Path) -> bool {
+        if !link.is_symlink() {
+            return false;
+        }
+        match (std::fs::canonicalize(link), std::fs::canonicalize(expected)) {
+            (Ok(actual), Ok(expected)) => actual == expected,
+            _ => false,
+        }
+    }
+
+    /// Create a link at `link` pointing to `source`, refusing to replace
+    /// anything that is already there.
+    pub fn create_link(source: &Path, link: &Path) -> Result<(), String> {
+        if link.exists() || link.is_symlink() {
+            return Err("Target location already has a file or folder with this name".to_string());
+        }
+
+        // Ensure target directory exists
+        Self::ensure_parent(link)?;
+        Self::create(source, link).map_err(Self::format_error)
+    }
+
+    /// Create the parent directory of `path` if it does not exist yet.
+    pub fn ensure_parent(path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            if !parent.exists() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create target directory: {}", e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Collect the links for every enabled item in a profile that applies to
+    /// this machine, using the best matching alternate of each item.
+    pub fn profile_links(profile: &Profile) -> Vec<LinkEntry> {
+        Self::layered_links(profile).0
+    }
+
+    /// Split the links of a profile into those that should exist and those
+    /// overridden by a source with a higher priority.
+    ///
+    /// Sources later in the profile take priority, so when two of them link
+    /// to the same path, the last one wins.
+    pub fn layered_links(profile: &Profile) -> (Vec<LinkEntry>, Vec<LinkEntry>) {
+        let mut winners = Vec::new();
+        let mut overridden = Vec::new();
+        let mut claimed = HashSet::new();
+
+        for source in profile.sources.iter().rev() {
+            for entry in Self::source_links(profile, source, &source.enabled_items()) {
+                if claimed.insert(entry.link.clone()) {
+                    winners.push(entry);
+                } else {
+                    overridden.push(entry);
+                }
+            }
+        }
+        winners.reverse();
+        overridden.reverse();
+
+        (winners, overridden)
+    }
+
+    /// Make the link at `link` point at the source that wins it, removing
+    /// the links of overridden sources first. The removed links are put back
+    /// when the winner can't be linked.
+    pub fn reconcile(
+        winners: &[LinkEntry],
+        overridden: &[LinkEntry],
+        link: &Path,
+    ) -> Result<(), String> {
+        let mut removed = Vec::new();
+        for entry in overridden.iter().filter(|entry| entry.link == link) {
+            if Self::points_to(&entry.link, &entry.source) {
+                Self::remove(&entry.link).map_err(Self::format_error)?;
+                removed.push(entry);
+            }
+        }
+
+        let Some(winner) = winners.iter().find(|entry| entry.link == link) else {
+            return Ok(());
+        };
+        if Self::points_to(&winner.link, &winner.source) {
+            return Ok(());
+        }
+
+        Self::create_link(&winner.target(), &winner.link).inspect_err(|_| {
+            for entry in removed {
+                let _ = Self::create(&entry.target(), &entry.link);
+            }
+        })
+    }
+
+    /// Collect the links for the given items of a source, skipping those that
+    /// don't apply to this machine or are no longer in the source folder.
+    pub fn source_links(
+        profile: &Profile,
+        source: &Source,
+        item_names: &[String],
+    ) -> Vec<LinkEntry> {
+        let host = Host::current();
+        let alternates = AlternateIndex::load(&source.get_source_path(&profile.variables));
+
+        item_names
+            .iter()
+            .filter(|item_name| host.applies(source, item_name) && alternates.contains(item_name))
+            .filter_map(|item_name| {
+                let file_name = alternates.resolve(item_name, &host)?;
+                Some(Self::link_entries(profile, source, item_name, &file_name))
+            })
+            .flatten()
+            .collect()
+    }
+
+    /// Describe the links for an item of a source, one per target folder,
+    /// pointing at `file_name`, which is the item itself or one of its
+    /// alternates.
+    pub fn link_entries(
+        profile: &Profile,
+        source: &Source,
+        item_name: &str,
+        file_name: &str,
+    ) -> Vec<LinkEntry> {
+        let item_source = source.get_source_path(&profile.variables).join(file_name);
+        let mode = source
+            .item(item_name)
+            .map(|item| item.link_mode)
+            .unwrap_or_default();
+
+        source
+            .link_paths(item_name, &profile.base_path, &profile.variables)
+            .into_iter()
+            .map(|link| LinkEntry {
+                source_id: source.id.clone(),
+                item_name: item_name.to_string(),
+                source: item_source.clone(),
+                link,
+                mode,
+            })
+            .collect()
+    }
+}
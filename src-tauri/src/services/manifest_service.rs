@@ -0,0 +1,148 @@
+use crate::models::{Profile, Source, SourceManifest, SymlinkResult};
+use crate::services::link_service::LinkEntry;
+use crate::services::{AlternateIndex, DependencyService, Host, LinkService};
+use std::fs;
+use std::path::Path;
+
+pub const MANIFEST_FILE_NAME: &str = ".symlinks.toml";
+
+pub struct ManifestService;
+
+impl ManifestService {
+    /// Read the manifest of a source directory, if it has one.
+    pub fn load(source_dir: &Path) -> Result<Option<SourceManifest>, String> {
+        let path = source_dir.join(MANIFEST_FILE_NAME);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", MANIFEST_FILE_NAME, e))?;
+
+        toml::from_str(&content)
+            .map(Some)
+            .map_err(|e| format!("Failed to parse {}: {}", MANIFEST_FILE_NAME, e))
+    }
+
+    /// Take over the layout a manifest declares for a source.
+    ///
+    /// The manifest target is used when the source has no target of its own
+    /// or still has the one taken from the manifest before. Enabled items the
+    /// manifest ignores are disabled; their links are left to the caller.
+    pub fn configure(source: &mut Source, manifest: &SourceManifest) {
+        let target = manifest.target.as_ref().map(|target| {
+            // Keep `~` and variables unexpanded so they resolve at use time
+            if target.starts_with(['~', '$']) || Path::new(target).is_absolute() {
+                target.clone()
+            } else {
+                Path::new(&source.source_path)
+                    .join(target)
+                    .to_string_lossy()
+                    .to_string()
+            }
+        });
+        if source.target_path.is_none() || source.target_path == source.manifest_target {
+            source.target_path = target.clone();
+        }
+        source.manifest_target = target;
+
+        source.ignore = manifest.ignore.clone();
+        source.link_names = manifest.names.clone();
+        for item_name in source.enabled_items() {
+            if source.is_ignored(&item_name) {
+                source.set_enabled(&item_name, false);
+            }
+        }
+    }
+
+    /// Link the items a manifest enables, for a source of `profile` or one
+    /// about to be added to it.
+    ///
+    /// Items that can't be linked or are incompatible with enabled items are
+    /// reported and left disabled, items that don't apply to this machine are
+    /// enabled without linking them.
+    pub fn enable_items(
+        profile: &Profile,
+        source: &mut Source,
+        manifest: &SourceManifest,
+    ) -> Vec<SymlinkResult> {
+        let source_dir = source.get_source_path(&profile.variables);
+
+        let host = Host::current();
+        let alternates = AlternateIndex::load(&source_dir);
+        let mut results = Vec::new();
+
+        for item_name in &manifest.enabled {
+            if source.is_enabled(item_name) || source.is_ignored(item_name) {
+                continue;
+            }
+
+            let file_name = alternates
+                .resolve(item_name, &host)
+                .filter(|_| host.applies(source, item_name));
+
+            // Check against the source as it is by now, as earlier items of
+            // the manifest may have been enabled
+            let mut composed = profile.clone();
+            composed.sources.retain(|s| s.id != source.id);
+            composed.sources.push(source.clone());
+            let conflicts = DependencyService::conflicts(&composed, &source.id, item_name);
+
+            let result = if !source_dir.join(item_name).exists()
+                && alternates.variants(item_name).is_empty()
+            {
+                Err(format!("Item does not exist in source: {}", item_name))
+            } else if let Some(error) = DependencyService::conflict_error(&conflicts) {
+                Err(error)
+            } else if let Some(file_name) = file_name {
+                let entries = source
+                    .link_paths(item_name, &profile.base_path, &profile.variables)
+                    .into_iter()
+                    .map(|link| LinkEntry {
+                        source_id: source.id.clone(),
+                        item_name: item_name.clone(),
+                        source: source_dir.join(&file_name),
+                        link,
+                        mode: source
+                            .item(item_name)
+                            .map(|item| item.link_mode)
+                            .unwrap_or_default(),
+                    })
+                    .collect::<Vec<_>>();
+                Self::link_targets(&entries)
+            } else {
+                Ok(())
+            };
+
+            if result.is_ok() {
+                source.set_enabled(item_name, true);
+            }
+            results.push(SymlinkResult {
+                success: result.is_ok(),
+                item_name: item_name.clone(),
+                error: result.err(),
+            });
+        }
+
+        results
+    }
+
+    /// Link an item into every target folder, removing the links created so
+    /// far if one fails.
+    fn link_targets(entries: &[LinkEntry]) -> Result<(), String> {
+        let mut created: Vec<&Path> = Vec::new();
+        for entry in entries {
+            if LinkService::points_to(&entry.link, &entry.source) {
+                continue;
+            }
+            if let Err(error) = LinkService::create_link(&entry.target(), &entry.link) {
+                for link in created {
+                    let _ = LinkService::remove(link);
+                }
+                return Err(error);
+            }
+            created.push(&entry.link);
+        }
+        Ok(())
+    }
+}
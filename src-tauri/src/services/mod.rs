@@ -1,3 +1,41 @@
+pub mod alternates;
+pub mod config_migration;
 pub mod config_service;
+pub mod config_validator;
+pub mod config_watcher;
+pub mod dependency_service;
+pub mod events;
+pub mod host;
+pub mod importer;
+pub mod item_identity;
+pub mod link_service;
+pub mod manifest_service;
+pub mod operation_queue;
+pub mod operation_registry;
+pub mod profile_bundle;
+pub mod script_exporter;
+pub mod shared_config;
+pub mod snapshot_service;
+pub mod source_watcher;
+pub mod target_audit;
 
+pub use alternates::AlternateIndex;
+pub use config_migration::ConfigMigration;
 pub use config_service::ConfigService;
+pub use config_validator::ConfigValidator;
+pub use config_watcher::ConfigWatcher;
+pub use dependency_service::DependencyService;
+pub use events::AppEvent;
+pub use host::Host;
+pub use importer::Importer;
+pub use item_identity::ItemIdentity;
+pub use link_service::LinkService;
+pub use manifest_service::ManifestService;
+pub use operation_queue::OperationQueue;
+pub use operation_registry::OperationRegistry;
+pub use profile_bundle::ProfileBundleService;
+pub use script_exporter::ScriptExporter;
+pub use shared_config::SharedConfig;
+pub use snapshot_service::SnapshotService;
+pub use source_watcher::SourceWatcher;
+pub use target_audit::TargetAudit;
@@ -0,0 +1,287 @@
+use crate::models::{
+    AppConfig, ConfigChanged, ConfigConflict, ConfigReloadFailed, ConfigSaveFailed,
+};
+use crate::services::{AppEvent, ConfigService, ConfigValidator, SharedConfig};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::SystemTime;
+use tauri::AppHandle;
+
+type Job = Box<dyn FnOnce(&mut Worker) + Send>;
+
+/// Serializes every mutating operation onto a single worker thread.
+///
+/// Each operation runs with exclusive access to the worker's copy of the
+/// config, so the config update, the filesystem work and the save happen as
+/// one unit. If the operation fails, the copy is restored to its previous
+/// state. Readers are served the last committed config from a
+/// [`SharedConfig`] in the meantime.
+///
+/// Once an operation is committed, `config-changed` is emitted if the config
+/// was modified, followed by any events the operation queued. If the config
+/// can't be saved, the changes are kept in memory and `config-save-failed`
+/// follows.
+///
+/// Edits made to the config file outside the app are picked up before each
+/// operation runs, so the operation is applied on top of them instead of
+/// overwriting them.
+#[derive(Clone)]
+pub struct OperationQueue {
+    sender: Sender<Job>,
+}
+
+/// Identifies a version of the config file on disk.
+type FileStamp = Option<(SystemTime, u64)>;
+
+struct Worker {
+    app: AppHandle,
+    /// The config operations work on.
+    config: AppConfig,
+    /// Where the config is published for readers once it is committed.
+    shared: SharedConfig,
+    config_path: PathBuf,
+    /// Stamp of the config file as of the last load or save.
+    synced_stamp: FileStamp,
+    /// Set when the in-memory config holds changes that could not be saved.
+    dirty: bool,
+    /// Version found on disk while `dirty` was set, awaiting resolution.
+    conflict: Option<AppConfig>,
+    /// Set when the file on disk could not be loaded, until it can again.
+    reload_failed: bool,
+    /// Set when the app started with defaults in place of a file that could
+    /// not be loaded nor moved aside, until the file can be loaded. Saving
+    /// would overwrite it, so operations are refused meanwhile.
+    read_only: bool,
+}
+
+impl OperationQueue {
+    pub fn spawn(
+        app: AppHandle,
+        config: AppConfig,
+        shared: SharedConfig,
+        config_path: PathBuf,
+        read_only: bool,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let worker = Worker {
+            app,
+            config,
+            shared,
+            synced_stamp: file_stamp(&config_path),
+            config_path,
+            dirty: false,
+            conflict: None,
+            reload_failed: false,
+            read_only,
+        };
+
+        thread::Builder::new()
+            .name("operation-queue".to_string())
+            .spawn(move || worker.run(receiver))
+            .expect("Failed to spawn operation queue worker");
+
+        Self { sender }
+    }
+
+    /// Run an operation on the worker and wait for its result.
+    ///
+    /// The config is saved after the operation succeeds. Events pushed by the
+    /// operation are only emitted if it succeeds.
+    pub fn execute<T, F>(&self, operation: F) -> Result<T, String>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut AppConfig, &mut Vec<AppEvent>) -> Result<T, String> + Send + 'static,
+    {
+        self.submit(move |worker| worker.transaction(operation))
+    }
+
+    /// Pick up changes made to the config file outside the app.
+    pub fn reload_from_disk(&self) -> Result<(), String> {
+        self.submit(|worker| worker.refresh_from_disk())
+    }
+
+    /// Settle a conflict between unsaved in-memory changes and an external
+    /// edit, either by taking the file on disk or by overwriting it.
+    pub fn resolve_conflict(&self, keep_disk: bool) -> Result<(), String> {
+        self.submit(move |worker| worker.resolve_conflict(keep_disk))
+    }
+
+    fn submit<T, F>(&self, job: F) -> Result<T, String>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Worker) -> Result<T, String> + Send + 'static,
+    {
+        let (reply_tx, reply_rx) = mpsc::channel();
+
+        let job: Job = Box::new(move |worker| {
+            let _ = reply_tx.send(job(worker));
+        });
+
+        self.sender
+            .send(job)
+            .map_err(|_| "Operation queue is not running".to_string())?;
+
+        reply_rx
+            .recv()
+            .map_err(|_| "Operation was aborted".to_string())?
+    }
+}
+
+impl Worker {
+    fn run(mut self, receiver: Receiver<Job>) {
+        for job in receiver {
+            job(&mut self);
+        }
+    }
+
+    fn transaction<T>(
+        &mut self,
+        operation: impl FnOnce(&mut AppConfig, &mut Vec<AppEvent>) -> Result<T, String>,
+    ) -> Result<T, String> {
+        self.refresh_from_disk()?;
+        if self.conflict.is_some() {
+            return Err(
+                "The config file was changed on disk while local changes were unsaved. \
+                 Resolve the conflict before making further changes."
+                    .to_string(),
+            );
+        }
+        if self.read_only {
+            return Err(
+                "The config file could not be loaded at startup, so changes can't be saved. \
+                 Fix the file to pick it up, or remove it and restart the app."
+                    .to_string(),
+            );
+        }
+
+        let previous = self.config.clone();
+        let mut events = Vec::new();
+
+        match operation(&mut self.config, &mut events) {
+            Ok(value) => {
+                // Changes that can't be saved are kept, as they are applied
+                // to the links already, and reported the same way
+                let changed = self.config != previous;
+                if changed {
+                    self.shared.publish(self.config.clone());
+                }
+                let was_dirty = self.dirty;
+                let saved = self.save();
+
+                // Saving changes kept from before lets the UI know they are
+                // saved now
+                if changed || (was_dirty && saved.is_ok()) {
+                    events.insert(0, config_changed(self.config.clone()));
+                }
+                if let Err(error) = &saved {
+                    events.push(AppEvent::ConfigSaveFailed(ConfigSaveFailed {
+                        error: error.clone(),
+                    }));
+                }
+                for event in &events {
+                    event.emit(&self.app);
+                }
+                saved.map(|_| value)
+            }
+            Err(e) => {
+                self.config = previous;
+                Err(e)
+            }
+        }
+    }
+
+    fn save(&mut self) -> Result<(), String> {
+        if let Err(e) = ConfigService::save(&self.config_path, &self.config) {
+            self.dirty = true;
+            return Err(e);
+        }
+        self.dirty = false;
+        self.synced_stamp = file_stamp(&self.config_path);
+        Ok(())
+    }
+
+    /// Reload the config file if it changed since it was last loaded or saved.
+    ///
+    /// Fails if the file changed but can't be parsed, so that a half-finished
+    /// manual edit is never overwritten.
+    fn refresh_from_disk(&mut self) -> Result<(), String> {
+        let stamp = file_stamp(&self.config_path);
+        if stamp == self.synced_stamp || stamp.is_none() {
+            return Ok(());
+        }
+
+        let disk_config = match ConfigService::load(&self.config_path) {
+            Ok(disk_config) => disk_config,
+            Err(e) => {
+                self.reload_failed = true;
+                AppEvent::ConfigReloadFailed(ConfigReloadFailed { error: e.clone() })
+                    .emit(&self.app);
+                return Err(format!(
+                    "The config file was changed on disk but could not be loaded: {}",
+                    e
+                ));
+            }
+        };
+        self.synced_stamp = stamp;
+        self.read_only = false;
+        let recovered = std::mem::take(&mut self.reload_failed);
+
+        if self.config == disk_config {
+            self.dirty = false;
+            self.conflict = None;
+
+            // Let the UI know the file can be loaded again
+            if recovered {
+                config_changed(disk_config).emit(&self.app);
+            }
+            return Ok(());
+        }
+
+        if self.dirty {
+            AppEvent::ConfigConflict(ConfigConflict {
+                disk_config: disk_config.clone(),
+            })
+            .emit(&self.app);
+            self.conflict = Some(disk_config);
+            return Ok(());
+        }
+
+        self.config = disk_config;
+        self.shared.publish(self.config.clone());
+        config_changed(self.config.clone()).emit(&self.app);
+        Ok(())
+    }
+
+    fn resolve_conflict(&mut self, keep_disk: bool) -> Result<(), String> {
+        let Some(disk_config) = self.conflict.take() else {
+            return Ok(());
+        };
+
+        if keep_disk {
+            self.config = disk_config;
+            self.dirty = false;
+            self.shared.publish(self.config.clone());
+        } else if let Err(e) = self.save() {
+            self.conflict = Some(disk_config);
+            return Err(e);
+        }
+
+        config_changed(self.config.clone()).emit(&self.app);
+        Ok(())
+    }
+}
+
+/// Build the `config-changed` event for a config, validating it so the
+/// problems of a file edited by hand are reported as well.
+fn config_changed(config: AppConfig) -> AppEvent {
+    AppEvent::ConfigChanged(ConfigChanged {
+        issues: ConfigValidator::validate(&config),
+        config,
+    })
+}
+
+fn file_stamp(path: &std::path::Path) -> FileStamp {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
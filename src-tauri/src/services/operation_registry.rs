@@ -0,0 +1,114 @@
+use crate::models::OperationProgress;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+
+pub const PROGRESS_EVENT: &str = "operation-progress";
+
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Tracks the cancellation tokens of running operations by their ID.
+#[derive(Default)]
+pub struct OperationRegistry {
+    tokens: Mutex<HashMap<String, CancellationToken>>,
+}
+
+impl OperationRegistry {
+    /// Start tracking an operation. Operations without an ID report no
+    /// progress and cannot be cancelled.
+    pub fn begin(&self, app: &AppHandle, operation_id: Option<String>) -> OperationContext {
+        let token = CancellationToken::default();
+        if let Some(id) = &operation_id {
+            if let Ok(mut tokens) = self.tokens.lock() {
+                tokens.insert(id.clone(), token.clone());
+            }
+        }
+
+        OperationContext {
+            operation_id,
+            app: app.clone(),
+            token,
+            done: 0,
+            total: 0,
+        }
+    }
+
+    /// Request cancellation. Returns false if no such operation is running.
+    pub fn cancel(&self, operation_id: &str) -> bool {
+        let tokens = match self.tokens.lock() {
+            Ok(tokens) => tokens,
+            Err(_) => return false,
+        };
+        match tokens.get(operation_id) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn finish(&self, operation_id: Option<&str>) {
+        if let (Some(id), Ok(mut tokens)) = (operation_id, self.tokens.lock()) {
+            tokens.remove(id);
+        }
+    }
+}
+
+/// Progress and cancellation state handed to a running operation.
+pub struct OperationContext {
+    operation_id: Option<String>,
+    app: AppHandle,
+    token: CancellationToken,
+    done: usize,
+    total: usize,
+}
+
+impl OperationContext {
+    pub fn operation_id(&self) -> &str {
+        self.operation_id.as_deref().unwrap_or_default()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    pub fn set_total(&mut self, total: usize) {
+        self.total = total;
+        self.emit(None);
+    }
+
+    /// Record one finished unit of work.
+    pub fn step(&mut self, current_path: &Path) {
+        self.done += 1;
+        self.emit(Some(current_path));
+    }
+
+    fn emit(&self, current_path: Option<&Path>) {
+        let Some(operation_id) = &self.operation_id else {
+            return;
+        };
+        let _ = self.app.emit(
+            PROGRESS_EVENT,
+            OperationProgress {
+                operation_id: operation_id.clone(),
+                done: self.done,
+                total: self.total,
+                current_path: current_path.map(|p| p.to_string_lossy().to_string()),
+            },
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use crate::models::{PathRemap, Profile, ProfileBundle, PROFILE_BUNDLE_VERSION};
+use crate::services::ConfigMigration;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use uuid::Uuid;
+
+pub struct ProfileBundleService;
+
+impl ProfileBundleService {
+    pub fn write(path: &Path, profiles: Vec<Profile>) -> Result<(), String> {
+        let bundle = ProfileBundle {
+            version: PROFILE_BUNDLE_VERSION,
+            profiles,
+        };
+
+        let content = serde_json::to_string_pretty(&bundle)
+            .map_err(|e| format!("Failed to serialize profiles: {}", e))?;
+
+        fs::write(path, content).map_err(|e| format!("Failed to write export file: {}", e))
+    }
+
+    pub fn read(path: &Path) -> Result<ProfileBundle, String> {
+        let content =
+            fs::read_to_string(path).map_err(|e| format!("Failed to read export file: {}", e))?;
+
+        let mut value: Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse export file: {}", e))?;
+
+        let version = ConfigMigration::version(&value);
+        if version > PROFILE_BUNDLE_VERSION {
+            return Err(format!(
+                "Export file version {} is newer than supported version {}",
+                version, PROFILE_BUNDLE_VERSION
+            ));
+        }
+        if let Some(profiles) = value.get_mut("profiles").and_then(Value::as_array_mut) {
+            profiles
+                .iter_mut()
+                .for_each(ConfigMigration::migrate_profile);
+        }
+
+        serde_json::from_value(value).map_err(|e| format!("Failed to parse export file: {}", e))
+    }
+
+    /// Prepare bundled profiles for import: give every profile and source a
+    /// fresh ID and rewrite their paths using the first matching remap.
+    /// Variables are rewritten the same way, as they mostly hold paths; values
+    /// that don't start with a remapped prefix stay as they are.
+    /// Includes of profiles in the bundle and references to items of its
+    /// sources follow their new IDs; includes of other profiles are dropped.
+    /// What is known about items on disk is dropped as well, as it describes
+    /// the machine the bundle is from.
+    pub fn prepare_import(bundle: ProfileBundle, remaps: &[PathRemap]) -> Vec<Profile> {
+        let new_ids = bundle
+            .profiles
+            .iter()
+            .map(|profile| (profile.id.clone(), Uuid::new_v4().to_string()))
+            .collect::<HashMap<_, _>>();
+        let new_source_ids = bundle
+            .profiles
+            .iter()
+            .flat_map(|profile| &profile.sources)
+            .map(|source| (source.id.clone(), Uuid::new_v4().to_string()))
+            .collect::<HashMap<_, _>>();
+
+        bundle
+            .profiles
+            .into_iter()
+            .map(|mut profile| {
+                profile.id = new_ids[&profile.id].clone();
+                // Includes of profiles that weren't exported with it can't be
+                // resolved here
+                profile.includes = profile
+                    .includes
+                    .iter()
+                    .filter_map(|include| new_ids.get(include).cloned())
+                    .collect();
+                profile.base_path = remap_path(&profile.base_path, remaps);
+                for value in profile.variables.values_mut() {
+                    *value = remap_path(value, remaps);
+                }
+
+                for source in &mut profile.sources {
+                    source.id = new_source_ids[&source.id].clone();
+                    source.remap_item_refs(&new_source_ids);
+                    // Inodes only mean something on the machine they're from
+                    source.identities.clear();
+                    source.source_path = remap_path(&source.source_path, remaps);
+                    source.target_path = source
+                        .target_path
+                        .as_deref()
+                        .map(|target| remap_path(target, remaps));
+                    source.manifest_target = source
+                        .manifest_target
+                        .as_deref()
+                        .map(|target| remap_path(target, remaps));
+                    for target in &mut source.additional_targets {
+                        *target = remap_path(target, remaps);
+                    }
+                    for item in &mut source.items {
+                        item.target_path = item
+                            .target_path
+                            .as_deref()
+                            .map(|target| remap_path(target, remaps));
+                    }
+                }
+                profile
+            })
+            .collect()
+    }
+}
+
+fn remap_path(path: &str, remaps: &[PathRemap]) -> String {
+    for remap in remaps {
+        if let Ok(rest) = Path::new(path).strip_prefix(&remap.from) {
+            if rest.as_os_str().is_empty() {
+                return remap.to.clone();
+            }
+            return Path::new(&remap.to)
+                .join(rest)
+                .to_string_lossy()
+                .to_string();
+        }
+    }
+    path.to_string()
+}
@@ -0,0 +1,504 @@
+use crate::models::{Condition, Profile};
+use crate::services::alternates::AlternateLabel;
+use crate::services::link_service::LinkEntry;
+use crate::services::{AlternateIndex, LinkService};
+use std::fmt::Write;
+use std::path::Path;
+
+/// Shell prelude shared by every exported script. It detects the machine for
+/// conditions, and its link functions perform the same checks as toggling an
+/// item in the app: an existing file or folder is never replaced, and only
+/// links pointing at their source are removed. Sources are written highest
+/// priority first, so the first item to claim a link name wins it.
+const SCRIPT_FUNCTIONS: &str = r#"failed=0
+claimed='|'
+
+case "$(uname -s)" in
+    Linux) os=linux family=unix ;;
+    Darwin) os=macos family=unix ;;
+    MINGW* | MSYS* | CYGWIN*) os=windows family=windows ;;
+    *) os=$(uname -s | tr '[:upper:]' '[:lower:]') family=unix ;;
+esac
+host=$( (hostname || uname -n) 2>/dev/null | tr '[:upper:]' '[:lower:]')
+short_host=${host%%.*}
+arch=$(uname -m)
+if [ "$arch" = arm64 ]; then
+    arch=aarch64
+fi
+user=$(id -un 2>/dev/null || echo "${USER:-}")
+
+os_is() {
+    for name in "$@"; do
+        if [ "$name" = "$os" ] || [ "$name" = "$family" ]; then
+            return 0
+        fi
+    done
+    return 1
+}
+
+host_is() {
+    case "$host" in
+        $1) return 0 ;;
+    esac
+    return 1
+}
+
+link_item() {
+    src=$1
+    dest=$2
+    target=${3:-$1}
+    case "$claimed" in
+        *"|$dest|"*)
+            echo "skipped  $dest: Overridden by a source with a higher priority"
+            return
+            ;;
+    esac
+    claimed="$claimed$dest|"
+    if [ -L "$dest" ] && [ "$(readlink "$dest")" = "$target" ]; then
+        echo "ok       $dest"
+        return
+    fi
+    if [ ! -e "$src" ] && [ ! -L "$src" ]; then
+        echo "failed   $dest: Source does not exist: $src" >&2
+        failed=1
+        return
+    fi
+    if [ -e "$dest" ] || [ -L "$dest" ]; then
+        echo "failed   $dest: Target location already has a file or folder with this name" >&2
+        failed=1
+        return
+    fi
+    if ! mkdir -p "$(dirname "$dest")"; then
+        echo "failed   $dest: Failed to create target directory" >&2
+        failed=1
+        return
+    fi
+    if ln -s "$target" "$dest"; then
+        echo "linked   $dest"
+    else
+        failed=1
+    fi
+}
+
+unlink_item() {
+    dest=$2
+    target=${3:-$1}
+    if [ ! -L "$dest" ]; then
+        return
+    fi
+    if [ "$(readlink "$dest")" != "$target" ]; then
+        echo "skipped  $dest: Link points somewhere else" >&2
+        return
+    fi
+    if rm "$dest"; then
+        echo "removed  $dest"
+    else
+        failed=1
+    fi
+}
+
+case "${1:-install}" in
+    install) action=link_item ;;
+    uninstall) action=unlink_item ;;
+    *)
+        echo "Usage: $0 [install|uninstall]" >&2
+        exit 2
+        ;;
+esac
+"#;
+
+/// Renders a profile as a standalone POSIX shell script, for machines where
+/// the app itself can't be installed.
+pub struct ScriptExporter;
+
+impl ScriptExporter {
+    /// Build a script that creates the profile's enabled links, or removes
+    /// them when run with `uninstall`. Paths in the home folder are written
+    /// relative to `$HOME`, so the script works for other users as well.
+    pub fn render(profile: &Profile) -> String {
+        Self::render_for_home(profile, dirs::home_dir().as_deref())
+    }
+
+    fn render_for_home(profile: &Profile, home: Option<&Path>) -> String {
+        let mut script = String::new();
+        script.push_str("#!/bin/sh\n");
+        let _ = writeln!(
+            script,
+            "# Links for profile \"{}\", generated by Symlink Manager.",
+            profile.name.replace('\n', " ")
+        );
+        script.push_str("#\n");
+        script.push_str("# Usage: sh <script> [install|uninstall]\n");
+        script.push_str("set -u\n\n");
+        script.push_str(SCRIPT_FUNCTIONS);
+        script.push('\n');
+
+        // Conditions and alternates are checked by the script, on the
+        // machine it runs on
+        for source in profile.sources.iter().rev() {
+            let source_dir = source.get_source_path(&profile.variables);
+            let alternates = AlternateIndex::load(&source_dir);
+
+            for item_name in &source.enabled_items() {
+                let conditions = source
+                    .conditions(item_name)
+                    .flat_map(condition_tests)
+                    .collect::<Vec<_>>();
+
+                let branches = item_branches(item_name, &alternates, &source_dir)
+                    .into_iter()
+                    .map(|(tests, file_name)| {
+                        let entries =
+                            LinkService::link_entries(profile, source, item_name, &file_name);
+                        let command = link_commands(&entries, home);
+                        ([conditions.as_slice(), tests.as_slice()].concat(), command)
+                    })
+                    .collect();
+                write_branches(&mut script, branches);
+            }
+        }
+
+        script.push_str("\nexit $failed\n");
+        script
+    }
+
+    /// Write the script for a profile and mark it executable.
+    pub fn write(path: &Path, profile: &Profile) -> Result<(), String> {
+        std::fs::write(path, Self::render(profile))
+            .map_err(|e| format!("Failed to write script: {}", e))?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+                .map_err(|e| format!("Failed to make script executable: {}", e))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// List the files an item can link to, most specific first, with the shell
+/// tests selecting each. The item itself is the fallback when it exists.
+fn item_branches(
+    item_name: &str,
+    alternates: &AlternateIndex,
+    source_dir: &Path,
+) -> Vec<(Vec<String>, String)> {
+    let variants = alternates.variants(item_name);
+    if variants.is_empty() {
+        return vec![(Vec::new(), item_name.to_string())];
+    }
+
+    let mut branches = variants
+        .iter()
+        .filter_map(|variant| {
+            let (_, conditions) = AlternateIndex::split(variant)?;
+            let labels = AlternateIndex::labels(conditions);
+            if labels.contains(&AlternateLabel::Unsupported) {
+                return None;
+            }
+            let tests = labels.iter().filter_map(label_test).collect();
+            Some((AlternateIndex::specificity(&labels), tests, variant.clone()))
+        })
+        .collect::<Vec<_>>();
+    branches.sort_by_key(|(specificity, _, _)| std::cmp::Reverse(*specificity));
+
+    let mut branches = branches
+        .into_iter()
+        .map(|(_, tests, file_name)| (tests, file_name))
+        .collect::<Vec<_>>();
+    let plain = source_dir.join(item_name);
+    if plain.exists() || plain.is_symlink() {
+        branches.push((Vec::new(), item_name.to_string()));
+    }
+    branches
+}
+
+/// Run `$action` for each link of an item, one target folder per line. The
+/// path stored in a relative link follows as a third argument.
+fn link_commands(entries: &[LinkEntry], home: Option<&Path>) -> String {
+    entries
+        .iter()
+        .map(|entry| {
+            let target = entry.target();
+            let mut command = format!(
+                "$action {} {}",
+                quote_path(&entry.source, home),
+                quote_path(&entry.link, home)
+            );
+            if target != entry.source {
+                let _ = write!(command, " {}", quote_path(&target, home));
+            }
+            command
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Translate an alternate label into a shell test. Labels that always match
+/// need no test.
+fn label_test(label: &AlternateLabel) -> Option<String> {
+    let test = match *label {
+        AlternateLabel::Default | AlternateLabel::Extension | AlternateLabel::Unsupported => {
+            return None
+        }
+        AlternateLabel::Arch(arch) => format!("[ \"$arch\" = {} ]", quote_str(arch)),
+        AlternateLabel::Os(os) => {
+            let os = os.to_lowercase();
+            let os = if os == "darwin" {
+                "macos".to_string()
+            } else {
+                os
+            };
+            format!("os_is {}", quote_str(&os))
+        }
+        AlternateLabel::Hostname(hostname) => {
+            let hostname = quote_str(&hostname.to_lowercase());
+            format!(
+                "{{ [ \"$host\" = {} ] || [ \"$short_host\" = {} ]; }}",
+                hostname, hostname
+            )
+        }
+        AlternateLabel::User(user) => format!("[ \"$user\" = {} ]", quote_str(user)),
+    };
+    Some(test)
+}
+
+/// Write the commands for an item, one per line, guarded by `if`/`elif`
+/// when they have tests. The first branch without tests ends the chain.
+fn write_branches(script: &mut String, branches: Vec<(Vec<String>, String)>) {
+    let mut opened = false;
+    for (tests, command) in branches {
+        let indented = command.replace('\n', "\n    ");
+        if tests.is_empty() {
+            if opened {
+                let _ = writeln!(script, "else\n    {}", indented);
+            } else {
+                let _ = writeln!(script, "{}", command);
+            }
+            break;
+        }
+
+        let keyword = if opened { "elif" } else { "if" };
+        let _ = writeln!(
+            script,
+            "{} {}; then\n    {}",
+            keyword,
+            tests.join(" && "),
+            indented
+        );
+        opened = true;
+    }
+    if opened {
+        script.push_str("fi\n");
+    }
+}
+
+/// Translate a condition into shell tests using the helpers of
+/// `SCRIPT_FUNCTIONS`.
+fn condition_tests(condition: &Condition) -> Vec<String> {
+    let mut tests = Vec::new();
+
+    if !condition.os.is_empty() {
+        let names = condition
+            .os
+            .iter()
+            .map(|os| quote_str(&os.to_lowercase()))
+            .collect::<Vec<_>>();
+        tests.push(format!("os_is {}", names.join(" ")));
+    }
+    if let Some(pattern) = &condition.hostname {
+        tests.push(format!("host_is {}", quote_str(&pattern.to_lowercase())));
+    }
+    for name in &condition.env {
+        if is_shell_name(name) {
+            tests.push(format!("[ -n \"${{{}:-}}\" ]", name));
+        } else {
+            // Not a variable the shell can look up, so it never matches
+            tests.push("false".to_string());
+        }
+    }
+
+    tests
+}
+
+fn is_shell_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Quote a path for the shell. Paths in the home folder are written as
+/// `"$HOME/..."`, anything else using single quotes.
+fn quote_path(path: &Path, home: Option<&Path>) -> String {
+    let Some(rest) = home.and_then(|home| path.strip_prefix(home).ok()) else {
+        return quote_str(&path.to_string_lossy());
+    };
+    if rest.as_os_str().is_empty() {
+        return "\"$HOME\"".to_string();
+    }
+
+    let mut quoted = String::from("\"$HOME/");
+    for c in rest.to_string_lossy().chars() {
+        if matches!(c, '"' | '$' | '`' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn quote_str(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn profile(value: serde_json::Value) -> Profile {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn writes_home_paths_relative_to_home() {
+        let profile = profile(json!({
+            "id": "work",
+            "name": "Work",
+            "basePath": "/home/alice",
+            "sources": [{
+                "id": "dotfiles",
+                "name": "Dotfiles",
+                "sourcePath": "/opt/dotfiles",
+                "targetPath": "/home/alice/.config",
+                "items": [{ "name": "nvim", "enabled": true }]
+            }]
+        }));
+
+        let script = ScriptExporter::render_for_home(&profile, Some(Path::new("/home/alice")));
+
+        assert!(script.contains("\n$action '/opt/dotfiles/nvim' \"$HOME/.config/nvim\"\n"));
+        assert!(!script.contains("/home/alice"));
+    }
+
+    #[test]
+    fn escapes_home_paths_for_double_quotes() {
+        let home = Some(Path::new("/home/alice"));
+
+        assert_eq!(quote_path(Path::new("/home/alice"), home), "\"$HOME\"");
+        assert_eq!(
+            quote_path(Path::new("/home/alice/$dir/`a\"b"), home),
+            "\"$HOME/\\$dir/\\`a\\\"b\""
+        );
+        assert_eq!(
+            quote_path(Path::new("/home/alicia/it's"), home),
+            "'/home/alicia/it'\\''s'"
+        );
+    }
+
+    #[test]
+    fn writes_higher_priority_sources_first() {
+        let profile = profile(json!({
+            "id": "work",
+            "name": "Work",
+            "basePath": "/home/alice",
+            "sources": [
+                {
+                    "id": "shared",
+                    "name": "Shared",
+                    "sourcePath": "/opt/shared",
+                    "items": [{ "name": "vimrc", "enabled": true }]
+                },
+                {
+                    "id": "local",
+                    "name": "Local",
+                    "sourcePath": "/opt/local",
+                    "items": [
+                        { "name": "vimrc", "enabled": true },
+                        { "name": "zshrc", "enabled": false }
+                    ]
+                }
+            ]
+        }));
+
+        let script = ScriptExporter::render_for_home(&profile, None);
+
+        let local = script.find("$action '/opt/local/vimrc'").unwrap();
+        let shared = script.find("$action '/opt/shared/vimrc'").unwrap();
+        assert!(local < shared);
+        assert!(!script.contains("zshrc"));
+    }
+
+    #[test]
+    fn guards_items_with_their_conditions() {
+        let profile = profile(json!({
+            "id": "work",
+            "name": "Work",
+            "basePath": "/home/alice",
+            "sources": [{
+                "id": "dotfiles",
+                "name": "Dotfiles",
+                "sourcePath": "/opt/dotfiles",
+                "condition": { "os": ["Linux", "macos"] },
+                "itemConditions": {
+                    "vimrc": { "hostname": "Work-*", "env": ["DISPLAY", "bad-name"] }
+                },
+                "items": [
+                    { "name": "vimrc", "enabled": true },
+                    { "name": "zshrc", "enabled": true }
+                ]
+            }]
+        }));
+
+        let script = ScriptExporter::render_for_home(&profile, None);
+
+        assert!(script.contains(
+            "\nif os_is 'linux' 'macos' && host_is 'work-*' && [ -n \"${DISPLAY:-}\" ] && false; then\n    \
+             $action '/opt/dotfiles/vimrc' '/home/alice/vimrc'\nfi\n"
+        ));
+        assert!(script.contains(
+            "\nif os_is 'linux' 'macos'; then\n    $action '/opt/dotfiles/zshrc' '/home/alice/zshrc'\nfi\n"
+        ));
+    }
+
+    #[test]
+    fn picks_alternates_on_the_machine_running_the_script() {
+        let dir = std::env::temp_dir().join(format!("symlink-manager-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in [
+            "gitconfig",
+            "gitconfig##os.Linux",
+            "gitconfig##os.Linux,hostname.work",
+        ] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+        let profile = profile(json!({
+            "id": "work",
+            "name": "Work",
+            "basePath": "/home/alice",
+            "sources": [{
+                "id": "dotfiles",
+                "name": "Dotfiles",
+                "sourcePath": dir,
+                "items": [{ "name": "gitconfig", "enabled": true }]
+            }]
+        }));
+
+        let script = ScriptExporter::render_for_home(&profile, None);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let source = |name: &str| quote_path(&dir.join(name), None);
+        assert!(script.contains(&format!(
+            "\nif os_is 'linux' && {{ [ \"$host\" = 'work' ] || [ \"$short_host\" = 'work' ]; }}; then\n    \
+             $action {} '/home/alice/gitconfig'\n\
+             elif os_is 'linux'; then\n    $action {} '/home/alice/gitconfig'\n\
+             else\n    $action {} '/home/alice/gitconfig'\nfi\n",
+            source("gitconfig##os.Linux,hostname.work"),
+            source("gitconfig##os.Linux"),
+            source("gitconfig")
+        )));
+    }
+}
@@ -0,0 +1,30 @@
+use crate::models::AppConfig;
+use std::sync::{Arc, Mutex};
+
+/// The committed config, for commands that only read it.
+///
+/// Operations work on the operation queue's own copy, which is published
+/// here once they succeed, so reading the config never waits for a running
+/// operation.
+#[derive(Clone)]
+pub struct SharedConfig(Arc<Mutex<Arc<AppConfig>>>);
+
+impl SharedConfig {
+    pub fn new(config: AppConfig) -> Self {
+        Self(Arc::new(Mutex::new(Arc::new(config))))
+    }
+
+    /// Get the config as of the last committed operation.
+    pub fn get(&self) -> Result<Arc<AppConfig>, String> {
+        self.0
+            .lock()
+            .map(|config| config.clone())
+            .map_err(|e| e.to_string())
+    }
+
+    pub(crate) fn publish(&self, config: AppConfig) {
+        if let Ok(mut shared) = self.0.lock() {
+            *shared = Arc::new(config);
+        }
+    }
+}
@@ -0,0 +1,421 @@
+use crate::models::{
+    Profile, Snapshot, SnapshotChange, SnapshotDiff, SnapshotItemDiff, SnapshotLink,
+    SnapshotSummary,
+};
+use crate::services::{ConfigMigration, LinkService};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+/// Stores snapshots as one JSON file each in the snapshot directory.
+pub struct SnapshotService;
+
+impl SnapshotService {
+    /// Record a profile and the links on disk of the enabled items of its
+    /// effective profile, which includes the sources of included profiles.
+    pub fn capture(profile: &Profile, effective: &Profile, label: Option<String>) -> Snapshot {
+        let (winners, _) = LinkService::layered_links(effective);
+        let links = winners
+            .into_iter()
+            .filter(|entry| LinkService::points_to(&entry.link, &entry.source))
+            .map(|entry| SnapshotLink {
+                link: entry.link.to_string_lossy().to_string(),
+                target: entry.source.to_string_lossy().to_string(),
+                source_id: entry.source_id,
+                item_name: entry.item_name,
+            })
+            .collect();
+
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+
+        Snapshot {
+            id: Uuid::new_v4().to_string(),
+            label,
+            created_at,
+            profile: profile.clone(),
+            links,
+        }
+    }
+
+    pub fn save(dir: &Path, snapshot: &Snapshot) -> Result<(), String> {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create snapshot directory: {}", e))?;
+
+        let content = serde_json::to_string_pretty(snapshot)
+            .map_err(|e| format!("Failed to serialize snapshot: {}", e))?;
+
+        fs::write(Self::path(dir, &snapshot.id)?, content)
+            .map_err(|e| format!("Failed to write snapshot: {}", e))
+    }
+
+    pub fn load(dir: &Path, snapshot_id: &str) -> Result<Snapshot, String> {
+        let content = fs::read_to_string(Self::path(dir, snapshot_id)?)
+            .map_err(|e| format!("Failed to read snapshot: {}", e))?;
+
+        parse(&content)
+    }
+
+    pub fn delete(dir: &Path, snapshot_id: &str) -> Result<(), String> {
+        fs::remove_file(Self::path(dir, snapshot_id)?)
+            .map_err(|e| format!("Failed to delete snapshot: {}", e))
+    }
+
+    /// List the snapshots of a profile, newest first. Unreadable files are
+    /// skipped.
+    pub fn list(dir: &Path, profile_id: &str) -> Vec<SnapshotSummary> {
+        let mut summaries = fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+            .filter_map(|content| parse(&content).ok())
+            .filter(|snapshot| snapshot.profile.id == profile_id)
+            .map(|snapshot| SnapshotSummary::from(&snapshot))
+            .collect::<Vec<_>>();
+
+        summaries.sort_by_key(|summary| std::cmp::Reverse(summary.created_at));
+        summaries
+    }
+
+    /// Compare a snapshot with the current state of its profile, which may
+    /// have been deleted since, given along with its effective profile.
+    ///
+    /// Only the profile's own items are enabled or disabled by restoring.
+    /// Links of included profiles are compared as long as their items are
+    /// still enabled.
+    pub fn diff(snapshot: &Snapshot, current: Option<(&Profile, &Profile)>) -> SnapshotDiff {
+        let mut items = Vec::new();
+
+        let (current, effective) = current.unzip();
+        let current_enabled = enabled_items(current);
+        let effective_enabled = enabled_items(effective);
+        let snapshot_enabled = enabled_items(Some(&snapshot.profile));
+
+        for source in &snapshot.profile.sources {
+            for item_name in &source.enabled_items() {
+                if !current_enabled.contains(&(source.id.as_str(), item_name.as_str())) {
+                    items.push(item_diff(
+                        &source.id,
+                        &source.name,
+                        item_name,
+                        SnapshotChange::Enable,
+                    ));
+                }
+            }
+        }
+
+        for source in current.iter().flat_map(|profile| &profile.sources) {
+            for item_name in &source.enabled_items() {
+                if !snapshot_enabled.contains(&(source.id.as_str(), item_name.as_str())) {
+                    items.push(item_diff(
+                        &source.id,
+                        &source.name,
+                        item_name,
+                        SnapshotChange::Disable,
+                    ));
+                }
+            }
+        }
+
+        for link in &snapshot.links {
+            let key = (link.source_id.as_str(), link.item_name.as_str());
+            if effective_enabled.contains(&key)
+                && !LinkService::points_to(Path::new(&link.link), Path::new(&link.target))
+            {
+                let source_name = snapshot
+                    .profile
+                    .sources
+                    .iter()
+                    .chain(effective.iter().flat_map(|profile| &profile.sources))
+                    .find(|s| s.id == link.source_id)
+                    .map(|s| s.name.as_str())
+                    .unwrap_or_default();
+                items.push(item_diff(
+                    &link.source_id,
+                    source_name,
+                    &link.item_name,
+                    SnapshotChange::Relink,
+                ));
+            }
+        }
+
+        SnapshotDiff {
+            profile_changed: current
+                .is_none_or(|current| settings(current) != settings(&snapshot.profile)),
+            items,
+        }
+    }
+
+    /// Build the path of a snapshot file, refusing IDs that would point
+    /// outside the snapshot directory.
+    fn path(dir: &Path, snapshot_id: &str) -> Result<PathBuf, String> {
+        if snapshot_id.is_empty()
+            || !snapshot_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            return Err(format!("Invalid snapshot ID: {}", snapshot_id));
+        }
+        Ok(dir.join(format!("{}.json", snapshot_id)))
+    }
+}
+
+/// Parse a snapshot, upgrading a profile saved by an older version.
+fn parse(content: &str) -> Result<Snapshot, String> {
+    let mut value: Value =
+        serde_json::from_str(content).map_err(|e| format!("Failed to parse snapshot: {}", e))?;
+    if let Some(profile) = value.get_mut("profile") {
+        ConfigMigration::migrate_profile(profile);
+    }
+    serde_json::from_value(value).map_err(|e| format!("Failed to parse snapshot: {}", e))
+}
+
+fn enabled_items(profile: Option<&Profile>) -> HashSet<(&str, &str)> {
+    profile
+        .iter()
+        .flat_map(|profile| &profile.sources)
+        .flat_map(|source| {
+            source
+                .items
+                .iter()
+                .filter(|item| item.enabled)
+                .map(|item| (source.id.as_str(), item.name.as_str()))
+        })
+        .collect()
+}
+
+fn item_diff(
+    source_id: &str,
+    source_name: &str,
+    item_name: &str,
+    change: SnapshotChange,
+) -> SnapshotItemDiff {
+    SnapshotItemDiff {
+        source_id: source_id.to_string(),
+        source_name: source_name.to_string(),
+        item_name: item_name.to_string(),
+        change,
+    }
+}
+
+/// A profile with all items disabled, to compare everything else.
+fn settings(profile: &Profile) -> Profile {
+    let mut profile = profile.clone();
+    for source in &mut profile.sources {
+        source.set_enabled_items(&[]);
+    }
+    profile
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn profile(id: &str, sources: serde_json::Value) -> Profile {
+        serde_json::from_value(json!({
+            "id": id,
+            "name": id,
+            "basePath": "/home/alice",
+            "sources": sources
+        }))
+        .unwrap()
+    }
+
+    fn snapshot(profile: Profile, links: Vec<SnapshotLink>) -> Snapshot {
+        Snapshot {
+            id: "snapshot".to_string(),
+            label: None,
+            created_at: 0,
+            profile,
+            links,
+        }
+    }
+
+    fn link(source_id: &str, item_name: &str, link: &Path, target: &Path) -> SnapshotLink {
+        SnapshotLink {
+            source_id: source_id.to_string(),
+            item_name: item_name.to_string(),
+            link: link.to_string_lossy().to_string(),
+            target: target.to_string_lossy().to_string(),
+        }
+    }
+
+    /// List the changes as source name, item name and change.
+    fn changes(diff: &SnapshotDiff) -> Vec<(&str, &str, SnapshotChange)> {
+        diff.items
+            .iter()
+            .map(|item| {
+                (
+                    item.source_name.as_str(),
+                    item.item_name.as_str(),
+                    item.change,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lists_items_to_enable_and_disable() {
+        let before = profile(
+            "home",
+            json!([{
+                "id": "a",
+                "name": "Dotfiles",
+                "sourcePath": "/opt/dotfiles",
+                "items": [
+                    { "name": "vimrc", "enabled": true },
+                    { "name": "zshrc", "enabled": true },
+                    { "name": "gitconfig" }
+                ]
+            }]),
+        );
+        let now = profile(
+            "home",
+            json!([{
+                "id": "a",
+                "name": "Dotfiles",
+                "sourcePath": "/opt/dotfiles",
+                "items": [
+                    { "name": "vimrc", "enabled": true },
+                    { "name": "zshrc" },
+                    { "name": "gitconfig", "enabled": true }
+                ]
+            }]),
+        );
+
+        let diff = SnapshotService::diff(&snapshot(before, Vec::new()), Some((&now, &now)));
+
+        assert!(!diff.profile_changed);
+        assert_eq!(
+            changes(&diff),
+            vec![
+                ("Dotfiles", "zshrc", SnapshotChange::Enable),
+                ("Dotfiles", "gitconfig", SnapshotChange::Disable),
+            ]
+        );
+    }
+
+    #[test]
+    fn enables_everything_of_a_deleted_profile() {
+        let before = profile(
+            "home",
+            json!([{
+                "id": "a",
+                "name": "Dotfiles",
+                "sourcePath": "/opt/dotfiles",
+                "items": [{ "name": "vimrc", "enabled": true }]
+            }]),
+        );
+
+        let diff = SnapshotService::diff(&snapshot(before, Vec::new()), None);
+
+        assert!(diff.profile_changed);
+        assert_eq!(
+            changes(&diff),
+            vec![("Dotfiles", "vimrc", SnapshotChange::Enable)]
+        );
+    }
+
+    #[test]
+    fn notices_changed_settings() {
+        let before = profile(
+            "home",
+            json!([{ "id": "a", "name": "Dotfiles", "sourcePath": "/opt/dotfiles" }]),
+        );
+        let now = profile(
+            "home",
+            json!([{ "id": "a", "name": "Dotfiles", "sourcePath": "/opt/moved" }]),
+        );
+
+        let diff = SnapshotService::diff(&snapshot(before, Vec::new()), Some((&now, &now)));
+
+        assert!(diff.profile_changed);
+        assert!(diff.items.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn relinks_items_whose_links_changed() {
+        let dir = std::env::temp_dir().join(format!("symlink-manager-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["vimrc", "zshrc", "gitconfig", "inputrc"] {
+            fs::write(dir.join(name), "").unwrap();
+        }
+        std::os::unix::fs::symlink(dir.join("vimrc"), dir.join(".vimrc")).unwrap();
+        std::os::unix::fs::symlink(dir.join("gitconfig"), dir.join(".zshrc")).unwrap();
+
+        let own = profile(
+            "home",
+            json!([{
+                "id": "a",
+                "name": "Dotfiles",
+                "sourcePath": dir,
+                "items": [
+                    { "name": "vimrc", "enabled": true },
+                    { "name": "zshrc", "enabled": true }
+                ]
+            }]),
+        );
+        let mut effective = profile(
+            "home",
+            json!([
+                {
+                    "id": "b",
+                    "name": "Shared",
+                    "sourcePath": dir,
+                    "items": [{ "name": "gitconfig", "enabled": true }]
+                },
+                {
+                    "id": "c",
+                    "name": "Other",
+                    "sourcePath": dir,
+                    "items": [{ "name": "inputrc" }]
+                }
+            ]),
+        );
+        effective.sources.extend(own.sources.clone());
+        let links = vec![
+            link("a", "vimrc", &dir.join(".vimrc"), &dir.join("vimrc")),
+            link("a", "zshrc", &dir.join(".zshrc"), &dir.join("zshrc")),
+            link(
+                "b",
+                "gitconfig",
+                &dir.join(".gitconfig"),
+                &dir.join("gitconfig"),
+            ),
+            link("c", "inputrc", &dir.join(".inputrc"), &dir.join("inputrc")),
+        ];
+
+        let diff = SnapshotService::diff(&snapshot(own.clone(), links), Some((&own, &effective)));
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(!diff.profile_changed);
+        assert_eq!(
+            changes(&diff),
+            vec![
+                ("Dotfiles", "zshrc", SnapshotChange::Relink),
+                ("Shared", "gitconfig", SnapshotChange::Relink),
+            ]
+        );
+    }
+
+    #[test]
+    fn refuses_snapshot_ids_outside_the_directory() {
+        let dir = Path::new("/snapshots");
+
+        assert_eq!(
+            SnapshotService::path(dir, "0b1f-42").unwrap(),
+            dir.join("0b1f-42.json")
+        );
+        assert!(SnapshotService::path(dir, "").is_err());
+        assert!(SnapshotService::path(dir, "../config").is_err());
+    }
+}
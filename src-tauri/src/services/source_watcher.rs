@@ -0,0 +1,155 @@
+use crate::models::{AppConfig, SourceWatchFailed, SymlinkResult};
+use crate::services::{AppEvent, OperationQueue, SharedConfig};
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+use tauri::AppHandle;
+
+/// How often the watched folders are matched up with the config.
+const RESYNC_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long to wait for more changes after one, so that copying or deleting
+/// many files syncs the source once.
+const SETTLE_DELAY: Duration = Duration::from_millis(500);
+
+/// Syncs the items of a source with its folder, given the profile and
+/// source ID.
+pub type SourceSync =
+    fn(&mut AppConfig, &mut Vec<AppEvent>, &str, &str) -> Result<Vec<SymlinkResult>, String>;
+
+/// Watches the folders of the active profile's sources, and syncs them
+/// through the operation queue when entries appear in, disappear from or are
+/// renamed in them.
+pub struct SourceWatcher;
+
+impl SourceWatcher {
+    pub fn spawn(app: AppHandle, config: SharedConfig, queue: OperationQueue, sync: SourceSync) {
+        thread::Builder::new()
+            .name("source-watcher".to_string())
+            .spawn(move || {
+                if let Err(e) = Self::watch(&config, &queue, sync) {
+                    AppEvent::SourceWatchFailed(SourceWatchFailed {
+                        error: format!("Changes to source folders are no longer picked up: {}", e),
+                    })
+                    .emit(&app);
+                }
+            })
+            .expect("Failed to spawn source watcher");
+    }
+
+    fn watch(
+        config: &SharedConfig,
+        queue: &OperationQueue,
+        sync: SourceSync,
+    ) -> Result<(), String> {
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender).map_err(|e| e.to_string())?;
+        let mut watched: HashMap<PathBuf, Vec<(String, String)>> = HashMap::new();
+
+        loop {
+            // Follow changes to the active profile and its sources. Folders
+            // that can't be watched yet are tried again on the next round.
+            let wanted = Self::watched_sources(config);
+            for folder in watched.keys() {
+                if !wanted.contains_key(folder) {
+                    let _ = watcher.unwatch(folder);
+                }
+            }
+            // Folders watched for the first time may have changed while they
+            // weren't, e.g. while the app was closed, so they are synced too
+            let mut changed = HashSet::new();
+            watched = wanted
+                .into_iter()
+                .filter(|(folder, sources)| {
+                    if watched.contains_key(folder) {
+                        return true;
+                    }
+                    let added = watcher.watch(folder, RecursiveMode::NonRecursive).is_ok();
+                    if added {
+                        changed.extend(sources.iter().cloned());
+                    }
+                    added
+                })
+                .collect();
+
+            let mut timeout = if changed.is_empty() {
+                RESYNC_INTERVAL
+            } else {
+                SETTLE_DELAY
+            };
+            loop {
+                match receiver.recv_timeout(timeout) {
+                    Ok(Ok(event)) => {
+                        changed.extend(Self::affected_sources(&event, &watched));
+                        if !changed.is_empty() {
+                            timeout = SETTLE_DELAY;
+                        }
+                    }
+                    Ok(Err(_)) => {}
+                    Err(RecvTimeoutError::Timeout) => break,
+                    Err(RecvTimeoutError::Disconnected) => return Ok(()),
+                }
+            }
+
+            for (profile_id, source_id) in changed {
+                // Failures show up as the status of the items
+                let _ = queue
+                    .execute(move |config, events| sync(config, events, &profile_id, &source_id));
+            }
+        }
+    }
+
+    /// Map the folders to watch to the profile and source IDs they belong
+    /// to.
+    fn watched_sources(config: &SharedConfig) -> HashMap<PathBuf, Vec<(String, String)>> {
+        let mut watched: HashMap<PathBuf, Vec<(String, String)>> = HashMap::new();
+        let Ok(config) = config.get() else {
+            return watched;
+        };
+        let Some(profile) = config
+            .active_profile_id
+            .as_ref()
+            .and_then(|id| config.effective_profile(id).ok())
+        else {
+            return watched;
+        };
+
+        for source in &profile.sources {
+            watched
+                .entry(canonical(&source.get_source_path(&profile.variables)))
+                .or_default()
+                .push((profile.id.clone(), source.id.clone()));
+        }
+        watched
+    }
+
+    /// Find the sources an entry appeared in or disappeared from.
+    fn affected_sources(
+        event: &Event,
+        watched: &HashMap<PathBuf, Vec<(String, String)>>,
+    ) -> Vec<(String, String)> {
+        if !matches!(
+            event.kind,
+            EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(_))
+        ) {
+            return Vec::new();
+        }
+
+        event
+            .paths
+            .iter()
+            .filter_map(|path| path.parent())
+            .filter_map(|folder| watched.get(&canonical(folder)))
+            .flatten()
+            .cloned()
+            .collect()
+    }
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
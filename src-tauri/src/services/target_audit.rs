@@ -0,0 +1,166 @@
+use crate::models::{Profile, Source, UnmanagedKind, UnmanagedLink};
+use crate::services::link_service::LinkEntry;
+use crate::services::{AlternateIndex, LinkService};
+use crate::utils::PathExpander;
+use std::path::{Path, PathBuf};
+
+/// Finds the symlinks in a profile's target folders that no enabled item
+/// accounts for, such as links left over from linking by hand.
+///
+/// Profiles may share target folders, so the links of the other profiles
+/// count as accounted for too.
+pub struct TargetAudit;
+
+impl TargetAudit {
+    /// List the unaccounted links in every target folder of a profile, one
+    /// folder after the other.
+    pub fn scan(profile: &Profile, others: &[Profile]) -> Vec<UnmanagedLink> {
+        let expected = Self::expected_links(profile, others);
+
+        let mut unmanaged = Vec::new();
+        for folder in Self::target_folders(profile) {
+            let mut links = std::fs::read_dir(&folder)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.is_symlink())
+                .collect::<Vec<_>>();
+            links.sort();
+
+            unmanaged.extend(
+                links
+                    .iter()
+                    .filter_map(|link| Self::inspect(profile, others, &expected, link)),
+            );
+        }
+        unmanaged
+    }
+
+    /// Collect the links a profile and the other profiles expect, including
+    /// overridden ones.
+    pub fn expected_links(profile: &Profile, others: &[Profile]) -> Vec<LinkEntry> {
+        std::iter::once(profile)
+            .chain(others)
+            .flat_map(|profile| {
+                let (winners, overridden) = LinkService::layered_links(profile);
+                winners.into_iter().chain(overridden)
+            })
+            .collect()
+    }
+
+    /// Check a single link against the links the profiles expect. Returns
+    /// `None` if it's accounted for, isn't a symlink or isn't directly in one
+    /// of the profile's target folders.
+    pub fn inspect(
+        profile: &Profile,
+        others: &[Profile],
+        expected: &[LinkEntry],
+        link: &Path,
+    ) -> Option<UnmanagedLink> {
+        let folder = link.parent()?;
+        if !Self::target_folders(profile)
+            .iter()
+            .any(|target_folder| target_folder == folder)
+        {
+            return None;
+        }
+
+        let target = std::fs::read_link(link).ok()?;
+        let managed = expected
+            .iter()
+            .any(|entry| entry.link == link && LinkService::points_to(link, &entry.source));
+        if managed {
+            return None;
+        }
+
+        // Sources of the audited profile come first, as it may include
+        // some of the other profiles
+        let resolved = resolve(link, &target);
+        let owner = std::iter::once(profile)
+            .chain(others)
+            .find_map(|owner| Some((owner, Self::owner(owner, &resolved)?)));
+        let foreign = owner
+            .as_ref()
+            .is_some_and(|(owner, _)| owner.id != profile.id);
+        let kind = if !link.exists() {
+            UnmanagedKind::Dangling
+        } else if foreign {
+            UnmanagedKind::Foreign
+        } else if owner.is_some() {
+            UnmanagedKind::Untracked
+        } else {
+            UnmanagedKind::Unknown
+        };
+
+        Some(UnmanagedLink {
+            link: link.to_string_lossy().to_string(),
+            target: target.to_string_lossy().to_string(),
+            kind,
+            source_id: owner.as_ref().map(|(_, (source, _))| source.id.clone()),
+            source_name: owner.as_ref().map(|(_, (source, _))| source.name.clone()),
+            profile_name: owner
+                .as_ref()
+                .filter(|_| foreign)
+                .map(|(owner, _)| owner.name.clone()),
+            item_name: owner.map(|(_, (_, item_name))| item_name),
+        })
+    }
+
+    /// Get every folder the profile links into: those of its sources and
+    /// those items of them have of their own.
+    pub fn target_folders(profile: &Profile) -> Vec<PathBuf> {
+        let mut folders = Vec::new();
+        for source in &profile.sources {
+            let item_folders = source
+                .items
+                .iter()
+                .filter_map(|item| item.target_path.as_deref())
+                .map(|path| PathExpander::expand(path, &profile.variables));
+            for folder in source
+                .get_target_paths(&profile.base_path, &profile.variables)
+                .into_iter()
+                .chain(item_folders)
+            {
+                if !folders.contains(&folder) {
+                    folders.push(folder);
+                }
+            }
+        }
+        folders
+    }
+
+    /// Find the source a resolved link target lies in, and the item of it.
+    /// With nested source folders, the innermost one wins.
+    fn owner<'a>(profile: &'a Profile, target: &Path) -> Option<(&'a Source, String)> {
+        profile
+            .sources
+            .iter()
+            .filter_map(|source| {
+                let source_dir =
+                    PathExpander::normalize(&source.get_source_path(&profile.variables));
+                let file_name = target.strip_prefix(&source_dir).ok()?.iter().next()?;
+                let file_name = file_name.to_string_lossy();
+                let item_name = AlternateIndex::split(&file_name)
+                    .map_or(file_name.as_ref(), |(item_name, _)| item_name)
+                    .to_string();
+                Some((source_dir.components().count(), source, item_name))
+            })
+            .max_by_key(|(depth, _, _)| *depth)
+            .map(|(_, source, item_name)| (source, item_name))
+    }
+}
+
+/// Resolve the target of a link to a full path for comparison. Only the
+/// folder is resolved, so an item that is itself a link still counts as
+/// being in its source.
+fn resolve(link: &Path, target: &Path) -> PathBuf {
+    let full = match link.parent() {
+        Some(link_dir) => link_dir.join(target),
+        None => target.to_path_buf(),
+    };
+    match (full.parent(), full.file_name()) {
+        (Some(folder), Some(file_name)) => PathExpander::normalize(folder).join(file_name),
+        _ => PathExpander::normalize(&full),
+    }
+}
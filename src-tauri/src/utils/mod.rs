@@ -0,0 +1,5 @@
+pub mod path_expander;
+pub mod yaml;
+
+pub use path_expander::PathExpander;
+pub use yaml::Yaml;
@@ -0,0 +1,302 @@
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+/// Expands `~`, `$NAME` and `${NAME}` in stored paths.
+///
+/// Variables are looked up in the profile first, then in the environment.
+/// `HOME` and the XDG base directories fall back to their usual defaults
+/// when unset. Unknown variables are left in place.
+pub struct PathExpander;
+
+impl PathExpander {
+    pub fn expand(raw: &str, variables: &BTreeMap<String, String>) -> PathBuf {
+        PathBuf::from(Self::expand_str(raw, variables))
+    }
+
+    pub fn expand_str(raw: &str, variables: &BTreeMap<String, String>) -> String {
+        let with_home = match raw.strip_prefix('~') {
+            Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => match home_dir() {
+                Some(home) => format!("{}{}", home, rest),
+                None => raw.to_string(),
+            },
+            _ => raw.to_string(),
+        };
+
+        let mut expanded = String::with_capacity(with_home.len());
+        let mut chars = with_home.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if c != '$' {
+                expanded.push(c);
+                continue;
+            }
+
+            let (name, braced) = match chars.peek() {
+                Some('$') => {
+                    chars.next();
+                    expanded.push('$');
+                    continue;
+                }
+                Some('{') => {
+                    chars.next();
+                    let mut name = String::new();
+                    for c in chars.by_ref() {
+                        if c == '}' {
+                            break;
+                        }
+                        name.push(c);
+                    }
+                    (name, true)
+                }
+                _ => {
+                    let mut name = String::new();
+                    while let Some(&c) = chars.peek() {
+                        if !(c.is_ascii_alphanumeric() || c == '_') {
+                            break;
+                        }
+                        name.push(c);
+                        chars.next();
+                    }
+                    (name, false)
+                }
+            };
+
+            match lookup(&name, variables) {
+                Some(value) => expanded.push_str(&value),
+                None if braced => expanded.push_str(&format!("${{{}}}", name)),
+                None => {
+                    expanded.push('$');
+                    expanded.push_str(&name);
+                }
+            }
+        }
+
+        expanded
+    }
+
+    /// List the variables in a path that can't be resolved.
+    pub fn undefined_variables(raw: &str, variables: &BTreeMap<String, String>) -> Vec<String> {
+        let mut undefined = Vec::new();
+        let mut rest = raw;
+
+        while let Some(start) = rest.find('$') {
+            rest = &rest[start + 1..];
+            if let Some(after) = rest.strip_prefix('$') {
+                rest = after;
+                continue;
+            }
+
+            let name = match rest.strip_prefix('{') {
+                Some(braced) => braced.split('}').next().unwrap_or_default(),
+                None => {
+                    let end = rest
+                        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                        .unwrap_or(rest.len());
+                    &rest[..end]
+                }
+            };
+
+            if !name.is_empty()
+                && lookup(name, variables).is_none()
+                && !undefined.iter().any(|n| n == name)
+            {
+                undefined.push(name.to_string());
+            }
+        }
+
+        undefined
+    }
+
+    /// Replace a leading home directory with `~`, so the path works for
+    /// other users.
+    pub fn contract_home(path: &Path) -> String {
+        if let Some(home) = home_dir() {
+            if let Ok(rest) = path.strip_prefix(&home) {
+                if rest.as_os_str().is_empty() {
+                    return "~".to_string();
+                }
+                return Path::new("~").join(rest).to_string_lossy().to_string();
+            }
+        }
+        path.to_string_lossy().to_string()
+    }
+
+    /// Resolve a path for comparison, falling back to a lexical cleanup when
+    /// it does not exist.
+    pub fn normalize(path: &Path) -> PathBuf {
+        if let Ok(canonical) = std::fs::canonicalize(path) {
+            return canonical;
+        }
+
+        clean(path)
+    }
+
+    /// Express `path` relative to the folder `base`, comparing both
+    /// lexically. Returns `None` when they don't share a root.
+    pub fn relative(path: &Path, base: &Path) -> Option<PathBuf> {
+        let path = clean(path);
+        let base = clean(base);
+        let path_parts = path.components().collect::<Vec<_>>();
+        let base_parts = base.components().collect::<Vec<_>>();
+
+        let common = path_parts
+            .iter()
+            .zip(&base_parts)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let rooted = path_parts
+            .first()
+            .is_some_and(|part| matches!(part, Component::RootDir | Component::Prefix(_)));
+        if common == 0 || !rooted {
+            return None;
+        }
+
+        let mut relative = PathBuf::new();
+        for _ in common..base_parts.len() {
+            relative.push("..");
+        }
+        for part in &path_parts[common..] {
+            relative.push(part);
+        }
+        if relative.as_os_str().is_empty() {
+            relative.push(".");
+        }
+        Some(relative)
+    }
+}
+
+/// Drop `.` and resolve `..` components without touching the disk.
+fn clean(path: &Path) -> PathBuf {
+    let mut cleaned = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                cleaned.pop();
+            }
+            other => cleaned.push(other),
+        }
+    }
+    cleaned
+}
+
+fn lookup(name: &str, variables: &BTreeMap<String, String>) -> Option<String> {
+    if let Some(value) = variables.get(name) {
+        // Profile variables may refer to the environment, but not to each other
+        return Some(PathExpander::expand_str(value, &BTreeMap::new()));
+    }
+
+    if let Some(value) = std::env::var_os(name).filter(|v| !v.is_empty()) {
+        return Some(value.to_string_lossy().to_string());
+    }
+
+    let home = home_dir()?;
+    let default = match name {
+        "HOME" => return Some(home),
+        "XDG_CONFIG_HOME" => ".config",
+        "XDG_DATA_HOME" => ".local/share",
+        "XDG_STATE_HOME" => ".local/state",
+        "XDG_CACHE_HOME" => ".cache",
+        _ => return None,
+    };
+    Some(Path::new(&home).join(default).to_string_lossy().to_string())
+}
+
+fn home_dir() -> Option<String> {
+    dirs::home_dir().map(|home| home.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relative(path: &str, base: &str) -> Option<PathBuf> {
+        PathExpander::relative(Path::new(path), Path::new(base))
+    }
+
+    fn variables(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn expands_profile_variables() {
+        let variables = variables(&[("XP", "/games/X-Plane 12"), ("SIM_USER", "pilot")]);
+
+        assert_eq!(
+            PathExpander::expand_str("$XP/Custom Scenery", &variables),
+            "/games/X-Plane 12/Custom Scenery"
+        );
+        assert_eq!(
+            PathExpander::expand_str("/home/${SIM_USER}_backup/$SIM_USER.cfg", &variables),
+            "/home/pilot_backup/pilot.cfg"
+        );
+    }
+
+    #[test]
+    fn profile_variables_take_precedence() {
+        let variables = variables(&[("HOME", "/home/profile")]);
+
+        assert_eq!(
+            PathExpander::expand_str("$HOME/.config", &variables),
+            "/home/profile/.config"
+        );
+    }
+
+    #[test]
+    fn keeps_unknown_variables_and_escaped_dollars() {
+        let variables = variables(&[("NAME", "nvim")]);
+
+        assert_eq!(
+            PathExpander::expand_str(
+                "/$SYMLINK_MANAGER_UNSET/${SYMLINK_MANAGER_UNSET}/$$NAME/$NAME",
+                &variables
+            ),
+            "/$SYMLINK_MANAGER_UNSET/${SYMLINK_MANAGER_UNSET}/$NAME/nvim"
+        );
+        assert_eq!(
+            PathExpander::undefined_variables(
+                "$SYMLINK_MANAGER_UNSET/${SYMLINK_MANAGER_UNSET}/$$OTHER/$NAME",
+                &variables
+            ),
+            vec!["SYMLINK_MANAGER_UNSET".to_string()]
+        );
+    }
+
+    #[test]
+    fn relative_climbs_out_of_the_base() {
+        assert_eq!(
+            relative("/home/me/dotfiles/nvim", "/home/me/.config"),
+            Some(PathBuf::from("../dotfiles/nvim"))
+        );
+        assert_eq!(
+            relative("/srv/dotfiles", "/home/me/.config"),
+            Some(PathBuf::from("../../../srv/dotfiles"))
+        );
+    }
+
+    #[test]
+    fn relative_descends_into_the_base() {
+        assert_eq!(
+            relative("/home/me/.config/nvim", "/home/me"),
+            Some(PathBuf::from(".config/nvim"))
+        );
+        assert_eq!(relative("/home/me", "/home/me"), Some(PathBuf::from(".")));
+    }
+
+    #[test]
+    fn relative_cleans_up_dots() {
+        assert_eq!(
+            relative("/home/me/./dotfiles/../shared/nvim", "/home/me/.config/"),
+            Some(PathBuf::from("../shared/nvim"))
+        );
+    }
+
+    #[test]
+    fn relative_needs_a_shared_root() {
+        assert_eq!(relative("dotfiles/nvim", "/home/me"), None);
+        assert_eq!(relative("dotfiles/nvim", "config"), None);
+    }
+}
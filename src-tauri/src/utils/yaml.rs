@@ -0,0 +1,427 @@
+/// A value read by [`Yaml::parse`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Yaml {
+    Null,
+    /// Plain or quoted scalar, kept as text
+    Scalar(String),
+    Sequence(Vec<Yaml>),
+    /// Entries in the order they appear
+    Mapping(Vec<(String, Yaml)>),
+}
+
+impl Yaml {
+    /// Parse the subset of YAML that config files such as dotbot's are
+    /// written in: block sequences and mappings, plain and quoted scalars,
+    /// flow collections on a single line, and comments. The contents of block
+    /// scalars (`|`, `>`) are skipped. Anchors, tags and multi-line flow
+    /// collections are not supported.
+    pub fn parse(text: &str) -> Result<Yaml, String> {
+        let lines = text
+            .lines()
+            .enumerate()
+            .filter_map(|(index, line)| {
+                let text = strip_comment(line).trim_end();
+                let content = text.trim_start_matches(' ');
+                let marker = matches!(content, "---" | "...") || content.starts_with('%');
+                (!content.is_empty() && !marker).then_some(Line {
+                    number: index + 1,
+                    indent: text.len() - content.len(),
+                    text: content,
+                })
+            })
+            .collect();
+
+        let mut parser = Parser { lines, pos: 0 };
+        let value = parser.node(0)?;
+        match parser.peek() {
+            Some(line) => Err(format!("Unexpected content on line {}", line.number)),
+            None => Ok(value),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Yaml> {
+        self.as_mapping()?
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Yaml::Scalar(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_sequence(&self) -> Option<&[Yaml]> {
+        match self {
+            Yaml::Sequence(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_mapping(&self) -> Option<&[(String, Yaml)]> {
+        match self {
+            Yaml::Mapping(entries) => Some(entries),
+            _ => None,
+        }
+    }
+}
+
+struct Line<'a> {
+    number: usize,
+    indent: usize,
+    text: &'a str,
+}
+
+struct Parser<'a> {
+    lines: Vec<Line<'a>>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Line<'a>> {
+        self.lines.get(self.pos)
+    }
+
+    /// Parse the block starting at the next line if it is indented by at
+    /// least `min_indent`.
+    fn node(&mut self, min_indent: usize) -> Result<Yaml, String> {
+        let Some(line) = self.peek().filter(|line| line.indent >= min_indent) else {
+            return Ok(Yaml::Null);
+        };
+        let (indent, text) = (line.indent, line.text);
+
+        if is_sequence_item(text) {
+            self.sequence(indent)
+        } else if split_key(text).is_some() {
+            self.mapping(indent)
+        } else {
+            self.pos += 1;
+            parse_inline(text)
+        }
+    }
+
+    fn sequence(&mut self, indent: usize) -> Result<Yaml, String> {
+        let mut items = Vec::new();
+
+        while let Some(line) = self
+            .peek()
+            .filter(|line| line.indent == indent && is_sequence_item(line.text))
+        {
+            let (number, text) = (line.number, line.text);
+            let rest = text[1..].trim_start_matches(' ');
+            let rest_indent = indent + text.len() - rest.len();
+
+            // A mapping or sequence can start on the line of its dash, in
+            // which case it continues at the column it starts at
+            if rest.is_empty() {
+                self.pos += 1;
+                items.push(self.node(indent + 1)?);
+            } else if is_sequence_item(rest) || split_key(rest).is_some() {
+                self.lines[self.pos] = Line {
+                    number,
+                    indent: rest_indent,
+                    text: rest,
+                };
+                items.push(self.node(rest_indent)?);
+            } else {
+                self.pos += 1;
+                items.push(parse_inline(rest)?);
+            }
+        }
+
+        Ok(Yaml::Sequence(items))
+    }
+
+    fn mapping(&mut self, indent: usize) -> Result<Yaml, String> {
+        let mut entries = Vec::new();
+
+        while let Some(line) = self
+            .peek()
+            .filter(|line| line.indent == indent && !is_sequence_item(line.text))
+        {
+            let number = line.number;
+            let (key, value) =
+                split_key(line.text).ok_or_else(|| format!("Expected a key on line {}", number))?;
+            self.pos += 1;
+
+            let value = if value.is_empty() {
+                // The items of a sequence may line up with its key
+                match self.peek() {
+                    Some(next) if next.indent == indent && is_sequence_item(next.text) => {
+                        self.sequence(indent)?
+                    }
+                    _ => self.node(indent + 1)?,
+                }
+            } else if value.starts_with(['|', '>']) {
+                while self.peek().is_some_and(|line| line.indent > indent) {
+                    self.pos += 1;
+                }
+                Yaml::Scalar(String::new())
+            } else {
+                parse_inline(value).map_err(|e| format!("{} on line {}", e, number))?
+            };
+            entries.push((key, value));
+        }
+
+        Ok(Yaml::Mapping(entries))
+    }
+}
+
+fn is_sequence_item(text: &str) -> bool {
+    text == "-" || text.starts_with("- ")
+}
+
+/// Split `key: value` into its unquoted key and the text of its value.
+fn split_key(text: &str) -> Option<(String, &str)> {
+    if text.starts_with(['{', '[']) {
+        return None;
+    }
+
+    let (key, rest) = if text.starts_with(['"', '\'']) {
+        let (key, rest) = unquote(text).ok()?;
+        (key, rest.trim_start())
+    } else {
+        let colon = text
+            .char_indices()
+            .find(|&(i, c)| {
+                c == ':' && text[i + 1..].chars().next().is_none_or(char::is_whitespace)
+            })
+            .map(|(i, _)| i)?;
+        (text[..colon].trim_end().to_string(), &text[colon..])
+    };
+
+    let value = rest.strip_prefix(':')?;
+    if !value.is_empty() && !value.starts_with(char::is_whitespace) {
+        return None;
+    }
+    Some((key, value.trim()))
+}
+
+fn parse_inline(text: &str) -> Result<Yaml, String> {
+    let text = text.trim();
+
+    if let Some(inner) = text.strip_prefix('{') {
+        let inner = inner.strip_suffix('}').ok_or("Unclosed flow mapping")?;
+        let entries = split_flow(inner)
+            .into_iter()
+            .map(|entry| match split_key(entry) {
+                Some((key, value)) => Ok((key, parse_inline(value)?)),
+                None => Ok((scalar_text(entry)?, Yaml::Null)),
+            })
+            .collect::<Result<_, String>>()?;
+        return Ok(Yaml::Mapping(entries));
+    }
+    if let Some(inner) = text.strip_prefix('[') {
+        let inner = inner.strip_suffix(']').ok_or("Unclosed flow sequence")?;
+        let items = split_flow(inner)
+            .into_iter()
+            .map(parse_inline)
+            .collect::<Result<_, String>>()?;
+        return Ok(Yaml::Sequence(items));
+    }
+
+    match text {
+        "" | "~" | "null" | "Null" | "NULL" => Ok(Yaml::Null),
+        _ => scalar_text(text).map(Yaml::Scalar),
+    }
+}
+
+fn scalar_text(text: &str) -> Result<String, String> {
+    if !text.starts_with(['"', '\'']) {
+        return Ok(text.to_string());
+    }
+    match unquote(text)? {
+        (value, "") => Ok(value),
+        _ => Err("Unexpected text after quoted scalar".to_string()),
+    }
+}
+
+/// Read the quoted string at the start of `text`, returning it along with
+/// the text that follows it.
+fn unquote(text: &str) -> Result<(String, &str), String> {
+    let quote = text.chars().next().filter(|c| matches!(c, '"' | '\''));
+    let quote = quote.ok_or("Expected a quoted scalar")?;
+    let mut value = String::new();
+    let mut chars = text.char_indices().skip(1).peekable();
+
+    while let Some((i, c)) = chars.next() {
+        if c == quote {
+            // Single quotes are escaped by doubling them
+            if quote == '\'' && chars.peek().is_some_and(|&(_, next)| next == '\'') {
+                chars.next();
+                value.push('\'');
+                continue;
+            }
+            return Ok((value, text[i + 1..].trim_end()));
+        }
+        if quote == '"' && c == '\\' {
+            match chars.next().map(|(_, escaped)| escaped) {
+                Some('n') => value.push('\n'),
+                Some('t') => value.push('\t'),
+                Some(escaped) => value.push(escaped),
+                None => break,
+            }
+            continue;
+        }
+        value.push(c);
+    }
+
+    Err("Unclosed quoted scalar".to_string())
+}
+
+/// Split the inside of a flow collection at its top-level commas.
+fn split_flow(inner: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut quote = None;
+    let mut start = 0;
+
+    for (i, c) in inner.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '{' | '[') => depth += 1,
+            (None, '}' | ']') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                parts.push(inner[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(inner[start..].trim());
+    parts.retain(|part| !part.is_empty());
+    parts
+}
+
+/// Cut off a comment, which starts with a `#` at the start of the line or
+/// after whitespace, outside of quotes.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    let mut previous = ' ';
+
+    for (i, c) in line.char_indices() {
+        match quote {
+            Some(_) if escaped => escaped = false,
+            Some('"') if c == '\\' => escaped = true,
+            Some('\'') if c == '\'' && line[i + 1..].starts_with('\'') => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '#' && previous.is_whitespace() => return &line[..i],
+            None if matches!(c, '"' | '\'')
+                && (previous.is_whitespace() || matches!(previous, '[' | '{' | ',')) =>
+            {
+                quote = Some(c)
+            }
+            None => {}
+        }
+        previous = c;
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scalar(value: &str) -> Yaml {
+        Yaml::Scalar(value.to_string())
+    }
+
+    #[test]
+    fn parses_block_collections() {
+        let yaml = Yaml::parse(
+            "# Links\n\
+             - defaults:\n    link:\n      relink: true\n\n\
+             - link:\n    ~/.vimrc: vimrc\n    ~/.config/:\n      path: config/\n      glob: true\n\
+             - shell:\n  - [git submodule update, Installing submodules]\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            yaml,
+            Yaml::Sequence(vec![
+                Yaml::Mapping(vec![(
+                    "defaults".to_string(),
+                    Yaml::Mapping(vec![(
+                        "link".to_string(),
+                        Yaml::Mapping(vec![("relink".to_string(), scalar("true"))])
+                    )])
+                )]),
+                Yaml::Mapping(vec![(
+                    "link".to_string(),
+                    Yaml::Mapping(vec![
+                        ("~/.vimrc".to_string(), scalar("vimrc")),
+                        (
+                            "~/.config/".to_string(),
+                            Yaml::Mapping(vec![
+                                ("path".to_string(), scalar("config/")),
+                                ("glob".to_string(), scalar("true")),
+                            ])
+                        ),
+                    ])
+                )]),
+                Yaml::Mapping(vec![(
+                    "shell".to_string(),
+                    Yaml::Sequence(vec![Yaml::Sequence(vec![
+                        scalar("git submodule update"),
+                        scalar("Installing submodules"),
+                    ])])
+                )]),
+            ])
+        );
+    }
+
+    #[test]
+    fn parses_quotes_comments_and_flow_mappings() {
+        let yaml = Yaml::parse(
+            "\"~/.my file\": 'it''s here' # the file\n\
+             ~/.bashrc: { path: \"bash/rc#1\", force: true }\n\
+             ~/.profile:\n\
+             ~/.zshrc: ~\n",
+        )
+        .unwrap();
+
+        assert_eq!(yaml.get("~/.my file"), Some(&scalar("it's here")));
+        assert_eq!(
+            yaml.get("~/.bashrc").and_then(|value| value.get("path")),
+            Some(&scalar("bash/rc#1"))
+        );
+        assert_eq!(yaml.get("~/.profile"), Some(&Yaml::Null));
+        assert_eq!(yaml.get("~/.zshrc"), Some(&Yaml::Null));
+    }
+
+    #[test]
+    fn reads_sequences_lined_up_with_their_key() {
+        let yaml = Yaml::parse("clean:\n- '~'\n- ~/.config\ncreate: [~/downloads]\n").unwrap();
+
+        assert_eq!(
+            yaml.get("clean"),
+            Some(&Yaml::Sequence(vec![scalar("~"), scalar("~/.config")]))
+        );
+        assert_eq!(
+            yaml.get("create"),
+            Some(&Yaml::Sequence(vec![scalar("~/downloads")]))
+        );
+    }
+
+    #[test]
+    fn skips_block_scalars() {
+        let yaml = Yaml::parse("- shell:\n    - command: |\n        echo one\n        echo two\n- link:\n    ~/.vimrc:\n").unwrap();
+
+        assert_eq!(
+            yaml.as_sequence().and_then(|items| items[1].get("link")),
+            Some(&Yaml::Mapping(vec![("~/.vimrc".to_string(), Yaml::Null)]))
+        );
+    }
+
+    #[test]
+    fn rejects_unexpected_indentation() {
+        assert!(Yaml::parse("link:\n    ~/.vimrc: vimrc\n  ~/.zshrc: zshrc\n").is_err());
+        assert!(Yaml::parse("link: { path: vimrc\n").is_err());
+    }
+}
@@ -1,6 +1,14 @@
-import { ref, watch, type Ref } from 'vue';
+import { onUnmounted, ref, watch, type Ref } from 'vue';
 import { invoke } from '@tauri-apps/api/core';
-import type { Item, SymlinkResult } from '@/types';
+import { listen } from '@tauri-apps/api/event';
+import type {
+  Item,
+  ItemConfig,
+  ItemStatusChangedEvent,
+  RelatedItem,
+  SymlinkResult,
+  ToggleCheck,
+} from '@/types';
 
 export function useItems(
   profileId: Ref<string | null>,
@@ -60,7 +68,9 @@ export function useItems(
         const item = items.value.find(i => i.name === itemName);
         if (item) {
           item.enabled = enabled;
-          item.status = enabled ? 'active' : 'inactive';
+          if (item.status !== 'notApplicable' && item.status !== 'overridden') {
+            item.status = enabled ? 'active' : 'inactive';
+          }
         }
       }
 
@@ -70,9 +80,93 @@ export function useItems(
     }
   }
 
+  // Find the requirements, incompatibilities and dependents to confirm first
+  async function checkToggle(itemName: string, enabled: boolean): Promise<ToggleCheck> {
+    return invoke<ToggleCheck>('check_item_toggle', {
+      profileId: profileId.value,
+      sourceId: sourceId.value,
+      itemName,
+      enabled,
+    });
+  }
+
+  // Toggle an item that may belong to another source of the profile
+  async function toggleRelated(item: RelatedItem, enabled: boolean): Promise<SymlinkResult> {
+    if (item.sourceId === sourceId.value) {
+      return toggleItem(item.itemName, enabled);
+    }
+    return invoke<SymlinkResult>('toggle_item', {
+      profileId: profileId.value,
+      sourceId: item.sourceId,
+      itemName: item.itemName,
+      enabled,
+    });
+  }
+
+  // Drop deleted items if the source does that automatically and run its
+  // auto-enable rules, which pick up new items
+  async function syncSource(): Promise<SymlinkResult[]> {
+    return invoke<SymlinkResult[]>('sync_source', {
+      profileId: profileId.value,
+      sourceId: sourceId.value,
+    });
+  }
+
+  // Remove the links of items deleted from the source folder, all of them
+  // unless names are given
+  async function cleanMissingItems(itemNames?: string[]): Promise<SymlinkResult[]> {
+    const results = await invoke<SymlinkResult[]>('clean_missing_items', {
+      profileId: profileId.value,
+      sourceId: sourceId.value,
+      itemNames: itemNames ?? null,
+    });
+    await loadItems();
+    return results;
+  }
+
+  // Links move with the settings, so statuses are reloaded afterwards
+  async function updateItem(item: ItemConfig): Promise<void> {
+    if (!profileId.value || !sourceId.value) {
+      throw new Error('No profile or source selected');
+    }
+
+    await invoke('update_item', {
+      profileId: profileId.value,
+      sourceId: sourceId.value,
+      item,
+    });
+    await loadItems();
+  }
+
   // Auto-reload when profile or source changes
   watch([profileId, sourceId], loadItems, { immediate: true });
 
+  // Pick up item changes made elsewhere (other windows, external tools)
+  const unlistenItemStatus = listen<ItemStatusChangedEvent>(
+    'item-status-changed',
+    (event) => {
+      const { profileId: changedProfile, sourceId: changedSource } = event.payload;
+      if (changedProfile !== profileId.value || changedSource !== sourceId.value) {
+        return;
+      }
+      const item = items.value.find((i) => i.name === event.payload.itemName);
+      if (item && item.status !== 'missing') {
+        item.enabled = event.payload.enabled;
+        item.status = event.payload.status;
+      } else {
+        // Enabled as soon as it appeared in the source folder, or dropped
+        // after it was deleted from it
+        loadItems();
+      }
+    }
+  );
+  const unlistenProfileApplied = listen('profile-applied', loadItems);
+
+  onUnmounted(() => {
+    unlistenItemStatus.then((unlisten) => unlisten());
+    unlistenProfileApplied.then((unlisten) => unlisten());
+  });
+
   return {
     items,
     isLoading,
@@ -80,5 +174,10 @@ export function useItems(
     error,
     loadItems,
     toggleItem,
+    checkToggle,
+    toggleRelated,
+    syncSource,
+    cleanMissingItems,
+    updateItem,
   };
 }
@@ -0,0 +1,71 @@
+import { ref } from 'vue';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import type { OperationProgress, OperationReport } from '@/types';
+import { useAppStore } from '@/stores/appStore';
+
+export function useOperations() {
+  const store = useAppStore();
+  const operationId = ref<string | null>(null);
+  const progress = ref<OperationProgress | null>(null);
+
+  async function run(
+    command: 'apply_profile' | 'switch_profile' | 'apply_preset' | 'restore_snapshot',
+    args: Record<string, string>
+  ): Promise<OperationReport> {
+    const id = crypto.randomUUID();
+    operationId.value = id;
+    progress.value = null;
+
+    const unlisten = await listen<OperationProgress>('operation-progress', (event) => {
+      if (event.payload.operationId === id) {
+        progress.value = event.payload;
+      }
+    });
+
+    try {
+      const report = await invoke<OperationReport>(command, {
+        ...args,
+        operationId: id,
+      });
+      await store.reloadConfig();
+      return report;
+    } finally {
+      unlisten();
+      operationId.value = null;
+    }
+  }
+
+  async function applyProfile(profileId: string): Promise<OperationReport> {
+    return run('apply_profile', { profileId });
+  }
+
+  async function switchProfile(profileId: string): Promise<OperationReport> {
+    return run('switch_profile', { profileId });
+  }
+
+  async function applyPreset(profileId: string, sourceId: string, name: string): Promise<OperationReport> {
+    return run('apply_preset', { profileId, sourceId, name });
+  }
+
+  async function restoreSnapshot(snapshotId: string): Promise<OperationReport> {
+    return run('restore_snapshot', { snapshotId });
+  }
+
+  async function cancel(): Promise<boolean> {
+    if (!operationId.value) {
+      return false;
+    }
+    return invoke<boolean>('cancel_operation', { operationId: operationId.value });
+  }
+
+  return {
+    operationId,
+    progress,
+    applyProfile,
+    switchProfile,
+    applyPreset,
+    restoreSnapshot,
+    cancel,
+  };
+}
@@ -0,0 +1,24 @@
+import { ref } from 'vue';
+import { invoke } from '@tauri-apps/api/core';
+import type { PathOwner } from '@/types';
+
+export function usePathLookup() {
+  const owners = ref<PathOwner[]>([]);
+  const isSearching = ref(false);
+
+  async function findOwner(path: string): Promise<PathOwner[]> {
+    isSearching.value = true;
+    try {
+      owners.value = await invoke<PathOwner[]>('find_path_owner', { path });
+      return owners.value;
+    } finally {
+      isSearching.value = false;
+    }
+  }
+
+  return {
+    owners,
+    isSearching,
+    findOwner,
+  };
+}
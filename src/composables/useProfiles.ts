@@ -1,6 +1,6 @@
 import { ref } from 'vue';
 import { invoke } from '@tauri-apps/api/core';
-import type { Profile } from '@/types';
+import type { ImportFormat, PathRemap, Profile, ProfileBundle, ProfilePatch } from '@/types';
 import { useAppStore } from '@/stores/appStore';
 
 export function useProfiles() {
@@ -20,10 +20,24 @@ export function useProfiles() {
     }
   }
 
-  async function updateProfile(profile: Profile): Promise<Profile> {
+  async function duplicateProfile(
+    profileId: string,
+    name?: string,
+    basePath?: string
+  ): Promise<Profile> {
+    const profile = await invoke<Profile>('duplicate_profile', {
+      profileId,
+      name: name || null,
+      basePath: basePath || null,
+    });
+    await store.reloadConfig();
+    return profile;
+  }
+
+  async function updateProfile(profileId: string, patch: ProfilePatch): Promise<Profile> {
     isUpdating.value = true;
     try {
-      const updated = await invoke<Profile>('update_profile', { profile });
+      const updated = await invoke<Profile>('update_profile', { profileId, patch });
       await store.reloadConfig();
       return updated;
     } finally {
@@ -46,6 +60,40 @@ export function useProfiles() {
     }
   }
 
+  async function exportProfiles(profileIds: string[], path: string): Promise<void> {
+    await invoke('export_profiles', { profileIds, path });
+  }
+
+  async function exportProfileScript(profileId: string, path: string): Promise<void> {
+    await invoke('export_profile_script', { profileId, path });
+  }
+
+  async function readProfileBundle(path: string): Promise<ProfileBundle> {
+    return invoke<ProfileBundle>('read_profile_bundle', { path });
+  }
+
+  async function importProfiles(path: string, remaps: PathRemap[]): Promise<Profile[]> {
+    const imported = await invoke<Profile[]>('import_profiles', { path, remaps });
+    await store.reloadConfig();
+    return imported;
+  }
+
+  async function importLayout(
+    format: ImportFormat,
+    path: string,
+    profileName: string,
+    targetPath?: string
+  ): Promise<Profile> {
+    const profile = await invoke<Profile>('import_layout', {
+      format,
+      path,
+      profileName,
+      targetPath: targetPath || null,
+    });
+    await store.reloadConfig();
+    return profile;
+  }
+
   return {
     profiles: () => store.profiles,
     activeProfile: () => store.activeProfile,
@@ -53,8 +101,14 @@ export function useProfiles() {
     isUpdating,
     isDeleting,
     createProfile,
+    duplicateProfile,
     updateProfile,
     deleteProfile,
+    exportProfiles,
+    exportProfileScript,
+    readProfileBundle,
+    importProfiles,
+    importLayout,
     setActiveProfile: store.setActiveProfile,
   };
 }
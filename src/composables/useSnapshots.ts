@@ -0,0 +1,38 @@
+import { ref } from 'vue';
+import { invoke } from '@tauri-apps/api/core';
+import type { SnapshotDiff, SnapshotSummary } from '@/types';
+
+export function useSnapshots() {
+  const snapshots = ref<SnapshotSummary[]>([]);
+
+  async function loadSnapshots(profileId: string): Promise<SnapshotSummary[]> {
+    snapshots.value = await invoke<SnapshotSummary[]>('list_snapshots', { profileId });
+    return snapshots.value;
+  }
+
+  async function createSnapshot(profileId: string, label?: string): Promise<SnapshotSummary> {
+    const snapshot = await invoke<SnapshotSummary>('create_snapshot', {
+      profileId,
+      label: label || null,
+    });
+    await loadSnapshots(profileId);
+    return snapshot;
+  }
+
+  async function diffSnapshot(snapshotId: string): Promise<SnapshotDiff> {
+    return invoke<SnapshotDiff>('diff_snapshot', { snapshotId });
+  }
+
+  async function deleteSnapshot(snapshot: SnapshotSummary): Promise<void> {
+    await invoke('delete_snapshot', { snapshotId: snapshot.id });
+    await loadSnapshots(snapshot.profileId);
+  }
+
+  return {
+    snapshots,
+    loadSnapshots,
+    createSnapshot,
+    diffSnapshot,
+    deleteSnapshot,
+  };
+}
@@ -1,6 +1,6 @@
 import { ref } from 'vue';
 import { invoke } from '@tauri-apps/api/core';
-import type { Source } from '@/types';
+import type { Source, SourcePatch, SourceRefresh } from '@/types';
 import { useAppStore } from '@/stores/appStore';
 
 export function useSources() {
@@ -14,26 +14,46 @@ export function useSources() {
     name: string,
     sourcePath: string,
     targetPath?: string
-  ): Promise<Source> {
+  ): Promise<SourceRefresh> {
     isCreating.value = true;
     try {
-      const source = await invoke<Source>('create_source', {
+      const created = await invoke<SourceRefresh>('create_source', {
         profileId,
         name,
         sourcePath,
         targetPath: targetPath || null,
       });
       await store.reloadConfig();
-      return source;
+      return created;
     } finally {
       isCreating.value = false;
     }
   }
 
-  async function updateSource(profileId: string, source: Source): Promise<Source> {
+  async function duplicateSource(
+    profileId: string,
+    sourceId: string,
+    name?: string,
+    targetPath?: string
+  ): Promise<Source> {
+    const source = await invoke<Source>('duplicate_source', {
+      profileId,
+      sourceId,
+      name: name || null,
+      targetPath: targetPath || null,
+    });
+    await store.reloadConfig();
+    return source;
+  }
+
+  async function updateSource(
+    profileId: string,
+    sourceId: string,
+    patch: SourcePatch
+  ): Promise<Source> {
     isUpdating.value = true;
     try {
-      const updated = await invoke<Source>('update_source', { profileId, source });
+      const updated = await invoke<Source>('update_source', { profileId, sourceId, patch });
       await store.reloadConfig();
       return updated;
     } finally {
@@ -51,12 +71,40 @@ export function useSources() {
     }
   }
 
+  async function refreshSource(profileId: string, sourceId: string): Promise<SourceRefresh> {
+    const refresh = await invoke<SourceRefresh>('refresh_source', { profileId, sourceId });
+    await store.reloadConfig();
+    return refresh;
+  }
+
+  async function reorderSources(profileId: string, sourceIds: string[]): Promise<void> {
+    await invoke('reorder_sources', { profileId, sourceIds });
+    await store.reloadConfig();
+  }
+
+  async function savePreset(profileId: string, sourceId: string, name: string): Promise<Source> {
+    const source = await invoke<Source>('save_preset', { profileId, sourceId, name });
+    await store.reloadConfig();
+    return source;
+  }
+
+  async function deletePreset(profileId: string, sourceId: string, name: string): Promise<Source> {
+    const source = await invoke<Source>('delete_preset', { profileId, sourceId, name });
+    await store.reloadConfig();
+    return source;
+  }
+
   return {
     isCreating,
     isUpdating,
     isDeleting,
     createSource,
+    duplicateSource,
     updateSource,
     deleteSource,
+    refreshSource,
+    reorderSources,
+    savePreset,
+    deletePreset,
   };
 }
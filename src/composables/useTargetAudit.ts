@@ -0,0 +1,39 @@
+import { ref } from 'vue';
+import { invoke } from '@tauri-apps/api/core';
+import type { SymlinkResult, UnmanagedLink } from '@/types';
+
+export function useTargetAudit() {
+  const links = ref<UnmanagedLink[]>([]);
+  const isScanning = ref(false);
+
+  async function auditTargets(profileId: string): Promise<UnmanagedLink[]> {
+    isScanning.value = true;
+    try {
+      links.value = await invoke<UnmanagedLink[]>('audit_targets', { profileId });
+      return links.value;
+    } finally {
+      isScanning.value = false;
+    }
+  }
+
+  // Both actions scan again afterwards, as adopting a link can account for others
+  async function deleteLinks(profileId: string, paths: string[]): Promise<SymlinkResult[]> {
+    const results = await invoke<SymlinkResult[]>('delete_unmanaged_links', { profileId, links: paths });
+    await auditTargets(profileId);
+    return results;
+  }
+
+  async function adoptLinks(profileId: string, paths: string[]): Promise<SymlinkResult[]> {
+    const results = await invoke<SymlinkResult[]>('adopt_unmanaged_links', { profileId, links: paths });
+    await auditTargets(profileId);
+    return results;
+  }
+
+  return {
+    links,
+    isScanning,
+    auditTargets,
+    deleteLinks,
+    adoptLinks,
+  };
+}
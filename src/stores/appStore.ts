@@ -1,7 +1,19 @@
 import { defineStore } from 'pinia';
 import { ref, computed } from 'vue';
-import type { AppConfig, PermissionStatus } from '@/types';
+import type {
+  AppConfig,
+  ConfigChangedEvent,
+  ConfigIssue,
+  ConfigConflictEvent,
+  ConfigReloadFailedEvent,
+  ConfigSaveFailedEvent,
+  SourceWatchFailedEvent,
+  PermissionStatus,
+  Profile,
+  Source,
+} from '@/types';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 
 export const useAppStore = defineStore('app', () => {
   // State
@@ -10,6 +22,11 @@ export const useAppStore = defineStore('app', () => {
   const permissionStatus = ref<PermissionStatus | null>(null);
   const isLoading = ref(false);
   const error = ref<string | null>(null);
+  const configConflict = ref<AppConfig | null>(null);
+  const issues = ref<ConfigIssue[]>([]);
+  const reloadError = ref<string | null>(null);
+  const saveError = ref<string | null>(null);
+  const watchError = ref<string | null>(null);
 
   // Getters
   const profiles = computed(() => config.value?.profiles ?? []);
@@ -18,10 +35,30 @@ export const useAppStore = defineStore('app', () => {
     profiles.value.find((p) => p.id === activeProfileId.value) ?? null
   );
 
+  // Sources of the profiles the active profile includes, lowest priority first
+  const includedSources = computed(() => {
+    const profile = activeProfile.value;
+    if (!profile) return [];
+    return composeSources(profile, []).filter((source) => !profile.sources.includes(source));
+  });
+
   const canCreateSymlinks = computed(
     () => permissionStatus.value?.canCreateSymlinks ?? true
   );
 
+  // Mirrors AppConfig::effective_profile: included sources come first and
+  // every source is only added once
+  function composeSources(profile: Profile, chain: string[]): Source[] {
+    if (chain.includes(profile.id)) return [];
+    const sources: Source[] = [];
+    for (const id of profile.includes ?? []) {
+      const included = profiles.value.find((p) => p.id === id);
+      if (included) sources.push(...composeSources(included, [...chain, profile.id]));
+    }
+    sources.push(...profile.sources);
+    return sources.filter((source, index) => sources.findIndex((s) => s.id === source.id) === index);
+  }
+
   // Actions
   async function initialize() {
     isLoading.value = true;
@@ -41,6 +78,33 @@ export const useAppStore = defineStore('app', () => {
       } else if (loadedConfig.profiles.length > 0) {
         activeProfileId.value = loadedConfig.profiles[0].id;
       }
+
+      await validateConfig();
+
+      // Keep in sync with changes made by other windows or external tools
+      await listen<ConfigChangedEvent>('config-changed', (event) => {
+        config.value = event.payload.config;
+        reloadError.value = null;
+        saveError.value = null;
+        // A file that couldn't be loaded at startup stays reported
+        issues.value = [
+          ...issues.value.filter((i) => i.kind === 'unreadableConfig'),
+          ...event.payload.issues,
+        ];
+      });
+      await listen<ConfigConflictEvent>('config-conflict', (event) => {
+        configConflict.value = event.payload.diskConfig;
+      });
+      await listen<ConfigReloadFailedEvent>('config-reload-failed', (event) => {
+        reloadError.value = event.payload.error;
+      });
+      // Changes that couldn't be saved are kept until the next save succeeds
+      await listen<ConfigSaveFailedEvent>('config-save-failed', (event) => {
+        saveError.value = event.payload.error;
+      });
+      await listen<SourceWatchFailedEvent>('source-watch-failed', (event) => {
+        watchError.value = event.payload.error;
+      });
     } catch (e) {
       error.value = String(e);
     } finally {
@@ -70,18 +134,39 @@ export const useAppStore = defineStore('app', () => {
     }
   }
 
+  async function validateConfig() {
+    try {
+      issues.value = await invoke<ConfigIssue[]>('validate_config');
+    } catch (e) {
+      console.error('Failed to validate config:', e);
+    }
+  }
+
+  async function resolveConfigConflict(keepDisk: boolean) {
+    await invoke('resolve_config_conflict', { keepDisk });
+    configConflict.value = null;
+  }
+
   return {
     config,
     activeProfileId,
     permissionStatus,
     isLoading,
     error,
+    configConflict,
+    issues,
+    reloadError,
+    saveError,
+    watchError,
     profiles,
     activeProfile,
+    includedSources,
     canCreateSymlinks,
     initialize,
     setActiveProfile,
     updateConfig,
     reloadConfig,
+    validateConfig,
+    resolveConfigConflict,
   };
 });
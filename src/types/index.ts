@@ -1,5 +1,5 @@
 // Item status enum
-export type ItemStatus = 'active' | 'inactive' | 'broken' | 'conflict';
+export type ItemStatus = 'active' | 'inactive' | 'broken' | 'conflict' | 'notApplicable' | 'overridden' | 'missing';
 
 // Single item in a source directory
 export interface Item {
@@ -9,6 +9,19 @@ export interface Item {
   status: ItemStatus;
   enabled: boolean;
   conflictSource?: string;
+  overriddenBy?: string;
+  alternates?: string[];
+  alternate?: string;
+  targets?: ItemTarget[];
+  settings?: ItemConfig;
+}
+
+// Status of an item's link in one of its source's target folders
+export interface ItemTarget {
+  path: string;
+  status: ItemStatus;
+  conflictSource?: string;
+  overriddenBy?: string;
 }
 
 // Source configuration
@@ -17,7 +30,75 @@ export interface Source {
   name: string;
   sourcePath: string;
   targetPath?: string;
-  enabledItems: string[];
+  manifestTarget?: string;
+  additionalTargets?: string[];
+  items?: ItemConfig[];
+  ignore?: string[];
+  linkNames?: Record<string, string>;
+  includeHidden?: boolean;
+  condition?: Condition;
+  itemConditions?: Record<string, Condition>;
+  presets?: Record<string, string[]>;
+  autoEnable?: AutoEnableRule[];
+  knownItems?: string[];
+  missingItems?: MissingItemPolicy;
+  identities?: Record<string, FileIdentity>;
+}
+
+// Settings of a source edited in its form, merged into the source as it is then
+export interface SourcePatch {
+  name: string;
+  sourcePath: string;
+  targetPath?: string;
+  additionalTargets: string[];
+  autoEnable: AutoEnableRule[];
+  missingItems: MissingItemPolicy;
+}
+
+// Recognizes an item's file or folder after it is renamed
+export interface FileIdentity {
+  inode?: string;
+  content?: string;
+  directory?: boolean;
+  size?: number;
+}
+
+// Picks items of a source to enable when they appear in its folder
+export type AutoEnableRule =
+  | { kind: 'newItems' }
+  | { kind: 'matching'; pattern: string }
+  | { kind: 'mirror' };
+
+// What happens to enabled items that disappear from the source folder
+export type MissingItemPolicy = 'ask' | 'remove';
+
+// How a link refers to its item
+export type LinkMode = 'absolute' | 'relative';
+
+// Settings of a single item of a source
+export interface ItemConfig {
+  name: string;
+  enabled: boolean;
+  linkName?: string;
+  targetPath?: string;
+  linkMode?: LinkMode;
+  notes?: string;
+  locked?: boolean;
+  requires?: ItemRef[];
+  conflicts?: ItemRef[];
+}
+
+// Refers to an item of the same source unless a source is given
+export interface ItemRef {
+  sourceId?: string;
+  name: string;
+}
+
+// Restricts a source or item to matching machines
+export interface Condition {
+  os?: string[];
+  hostname?: string;
+  env?: string[];
 }
 
 // Profile configuration
@@ -26,6 +107,15 @@ export interface Profile {
   name: string;
   basePath: string;
   sources: Source[];
+  variables?: Record<string, string>;
+  includes?: string[];
+}
+
+// Settings of a profile edited in its form, merged into the profile as it is then
+export interface ProfilePatch {
+  name: string;
+  basePath: string;
+  includes: string[];
 }
 
 // Root configuration
@@ -42,9 +132,167 @@ export interface SymlinkResult {
   error?: string;
 }
 
+// An item that toggling another one depends on or affects
+export interface RelatedItem {
+  sourceId: string;
+  sourceName: string;
+  itemName: string;
+}
+
+export interface ToggleCheck {
+  missingRequirements: RelatedItem[];
+  conflicts: RelatedItem[];
+  dependents: RelatedItem[];
+}
+
+export interface SourceRefresh {
+  source: Source;
+  results: SymlinkResult[];
+}
+
 export interface PermissionStatus {
   canCreateSymlinks: boolean;
   requiresElevation: boolean;
   isDeveloperMode: boolean;
   errorMessage?: string;
 }
+
+// Long-running operations
+export interface OperationProgress {
+  operationId: string;
+  done: number;
+  total: number;
+  currentPath?: string;
+}
+
+export interface OperationReport {
+  operationId: string;
+  cancelled: boolean;
+  results: SymlinkResult[];
+}
+
+// Profile snapshots
+export interface SnapshotSummary {
+  id: string;
+  profileId: string;
+  label?: string;
+  createdAt: number;
+  linkCount: number;
+}
+
+export type SnapshotChange = 'enable' | 'disable' | 'relink';
+
+export interface SnapshotItemDiff {
+  sourceId: string;
+  sourceName: string;
+  itemName: string;
+  change: SnapshotChange;
+}
+
+export interface SnapshotDiff {
+  profileChanged: boolean;
+  items: SnapshotItemDiff[];
+}
+
+// Links in target folders that no enabled item accounts for
+export type UnmanagedKind = 'dangling' | 'unknown' | 'untracked' | 'foreign';
+
+export interface UnmanagedLink {
+  link: string;
+  target: string;
+  kind: UnmanagedKind;
+  sourceId?: string;
+  sourceName?: string;
+  itemName?: string;
+  profileName?: string;
+}
+
+// Items a looked up path belongs to
+export type PathRole = 'link' | 'source';
+
+export interface PathOwner {
+  profileId: string;
+  profileName: string;
+  active: boolean;
+  sourceId: string;
+  sourceName: string;
+  itemName: string;
+  role: PathRole;
+  link?: string;
+  enabled: boolean;
+  status: ItemStatus;
+}
+
+// Backend events
+export interface ConfigChangedEvent {
+  config: AppConfig;
+  issues: ConfigIssue[];
+}
+
+export interface ProfileAppliedEvent {
+  profileId: string;
+  operationId: string;
+}
+
+export interface ItemStatusChangedEvent {
+  profileId: string;
+  sourceId: string;
+  itemName: string;
+  enabled: boolean;
+  status: ItemStatus;
+}
+
+export interface ConfigConflictEvent {
+  diskConfig: AppConfig;
+}
+
+export interface ConfigReloadFailedEvent {
+  error: string;
+}
+
+export interface ConfigSaveFailedEvent {
+  error: string;
+}
+
+export interface SourceWatchFailedEvent {
+  error: string;
+}
+
+// Config validation
+export type IssueSeverity = 'error' | 'warning';
+
+export type IssueKind =
+  | 'duplicateProfileId'
+  | 'duplicateSourceId'
+  | 'missingActiveProfile'
+  | 'targetInsideSource'
+  | 'duplicateSource'
+  | 'missingPath'
+  | 'undefinedVariable'
+  | 'duplicateEnabledItem'
+  | 'invalidCondition'
+  | 'invalidInclude'
+  | 'invalidItemReference'
+  | 'invalidAutoEnableRule'
+  | 'unreadableConfig';
+
+export interface ConfigIssue {
+  severity: IssueSeverity;
+  kind: IssueKind;
+  message: string;
+  profileId?: string;
+  sourceId?: string;
+}
+
+// Profile export/import
+export interface ProfileBundle {
+  version: number;
+  profiles: Profile[];
+}
+
+export interface PathRemap {
+  from: string;
+  to: string;
+}
+
+export type ImportFormat = 'stow' | 'dotbot' | 'lnScript';